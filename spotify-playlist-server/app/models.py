@@ -2,7 +2,7 @@
 from sqlalchemy import Column, String, Boolean, Integer, DateTime, Text, JSON
 from sqlalchemy.sql import func
 from pydantic import BaseModel, Field
-from typing import Optional, List, Literal
+from typing import Optional, List, Literal, Dict
 from datetime import datetime
 from app.database import Base
 
@@ -175,6 +175,16 @@ class Schedule(BaseModel):
         from_attributes = True
 
 
+class ConfigBundle(BaseModel):
+    """Configs and their schedules in one file, shared with the desktop app."""
+    format: str = "spotify-sorter-bundle"
+    version: int = 1
+    exported_at: Optional[datetime] = None
+    configs: List[DynamicPlaylistConfig]
+    schedules: List[Schedule] = Field(default_factory=list)
+    playlist_names: Dict[str, str] = Field(default_factory=dict)  # By playlist ID
+
+
 class RunHistory(BaseModel):
     """Schema for run history entry."""
     id: str
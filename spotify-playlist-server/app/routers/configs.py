@@ -2,10 +2,12 @@
 from fastapi import APIRouter, Depends, HTTPException, BackgroundTasks
 from sqlalchemy.orm import Session
 from typing import List
+from datetime import datetime
 
 from app.database import get_db
-from app.models import DynamicPlaylistConfig, RunHistory
+from app.models import DynamicPlaylistConfig, RunHistory, ConfigBundle
 from app.services.dynamic_playlist import DynamicPlaylistService
+from app.services.scheduler_service import SchedulerService
 
 router = APIRouter(prefix="/api/configs", tags=["configs"])
 
@@ -17,6 +19,55 @@ def get_configs(db: Session = Depends(get_db)):
     return service.get_all_configs()
 
 
+@router.get("/export", response_model=ConfigBundle)
+def export_configs(db: Session = Depends(get_db)):
+    """Export all configurations and schedules as a bundle."""
+    configs = DynamicPlaylistService(db).get_all_configs()
+    schedules = SchedulerService(db).get_all_schedules()
+    for schedule in schedules:
+        schedule.last_run = None
+        schedule.next_run = None
+
+    return ConfigBundle(
+        exported_at=datetime.now(),
+        configs=configs,
+        schedules=schedules,
+        playlist_names={
+            c.target_playlist_id: c.target_playlist_name
+            for c in configs if c.target_playlist_name
+        },
+    )
+
+
+@router.post("/import")
+def import_configs(bundle: ConfigBundle, db: Session = Depends(get_db)):
+    """Import a bundle from the desktop app or another server as new configurations."""
+    service = DynamicPlaylistService(db)
+    scheduler = SchedulerService(db)
+
+    id_map = {}
+    for config in bundle.configs:
+        old_id = config.id
+        config.id = None
+        if not config.target_playlist_name:
+            config.target_playlist_name = bundle.playlist_names.get(config.target_playlist_id, "")
+        saved = service.save_config(config)
+        if old_id:
+            id_map[old_id] = saved.id
+
+    schedule_count = 0
+    for schedule in bundle.schedules:
+        if schedule.config_id not in id_map:
+            continue
+        try:
+            scheduler.create_schedule(id_map[schedule.config_id], schedule.cron_expression, schedule.enabled)
+            schedule_count += 1
+        except ValueError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+
+    return {"configs": len(bundle.configs), "schedules": schedule_count}
+
+
 @router.get("/{config_id}", response_model=DynamicPlaylistConfig)
 def get_config(config_id: str, db: Session = Depends(get_db)):
     """Get a specific configuration."""
@@ -0,0 +1,47 @@
+use crate::storage::DataFile;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Tracks, artists and albums that dynamic playlists and version replacement
+/// never add. Artists and albums are matched by name, ignoring case.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BanList {
+    #[serde(default)]
+    pub track_uris: Vec<String>,
+    #[serde(default)]
+    pub artists: Vec<String>,
+    #[serde(default)]
+    pub albums: Vec<String>,
+}
+
+fn get_ban_list_path() -> PathBuf {
+    crate::profiles::current_profile_dir().join("ban_list.json")
+}
+
+const BAN_LIST_FILE: DataFile = DataFile { label: "ban list" };
+
+pub fn load_ban_list() -> BanList {
+    BAN_LIST_FILE.load(&get_ban_list_path())
+}
+
+pub fn save_ban_list(list: &BanList) -> Result<(), String> {
+    BAN_LIST_FILE.save(&get_ban_list_path(), list)
+}
+
+impl BanList {
+    /// Whether a track is banned. `artists` is the comma separated list of
+    /// credited artists, as tracks carry it.
+    pub fn bans(&self, uri: &str, artists: &str, album: &str) -> bool {
+        self.track_uris.iter().any(|u| u == uri)
+            || self
+                .albums
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(album.trim()))
+            || artists.split(',').any(|artist| {
+                self.artists
+                    .iter()
+                    .any(|banned| banned.eq_ignore_ascii_case(artist.trim()))
+            })
+    }
+}
@@ -0,0 +1,428 @@
+use crate::dynamic::{
+    DynamicPlaylistConfig, FilterConfig, ProcessingOptions, Source, SourceEntry, UpdateMode,
+};
+use crate::logic::SortRule;
+use crate::scheduler::DesktopSchedule;
+use crate::spotify::Playlist;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const BUNDLE_FORMAT: &str = "spotify-sorter-bundle";
+const BUNDLE_VERSION: u32 = 1;
+
+/// Dynamic configs and their schedules in one shareable file. Configs use
+/// the companion server's field names, so bundles move between the desktop
+/// app and the server; settings the server lacks ride along in `desktop`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    #[serde(default)]
+    pub format: String,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub exported_at: Option<String>,
+    pub configs: Vec<BundleConfig>,
+    #[serde(default)]
+    pub schedules: Vec<DesktopSchedule>,
+    /// Names of the playlists the configs use, by ID
+    #[serde(default)]
+    pub playlist_names: HashMap<String, String>,
+    /// Schedules turned off only because the server can't run their config;
+    /// the desktop app turns them back on when importing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paused_for_server: Vec<String>,
+}
+
+/// A config as the server stores it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleConfig {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    pub target_playlist_id: String,
+    #[serde(default)]
+    pub target_playlist_name: String,
+    #[serde(default)]
+    pub sources: Vec<ServerSource>,
+    #[serde(default)]
+    pub filters: ServerFilters,
+    #[serde(default = "default_update_mode")]
+    pub update_mode: String,
+    #[serde(default)]
+    pub sample_per_source: Option<usize>,
+    #[serde(default)]
+    pub include_liked_songs: bool,
+    #[serde(default)]
+    pub processing: ServerProcessing,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// The full desktop config, when exported by the desktop app
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<DynamicPlaylistConfig>,
+}
+
+fn default_update_mode() -> String {
+    "replace".to_string()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// The server only knows playlist and liked songs sources
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerSource {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ServerFilters {
+    #[serde(default)]
+    pub exclude_liked: bool,
+    #[serde(default)]
+    pub keyword_blacklist: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ServerProcessing {
+    #[serde(default)]
+    pub apply_sort: bool,
+    #[serde(default)]
+    pub apply_dupes: bool,
+    #[serde(default)]
+    pub apply_versions: bool,
+    #[serde(default)]
+    pub sort_rules: Vec<ServerSortRule>,
+    #[serde(default)]
+    pub dupe_preference: String,
+    #[serde(default)]
+    pub version_preference: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerSortRule {
+    pub criteria: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Why the server can't run a config, if it can't. Its fields would leave
+/// out the sources it lacks, and running that would empty the target.
+pub fn server_unsupported(config: &DynamicPlaylistConfig) -> Option<String> {
+    if config.source_expression.is_some() {
+        return Some("uses a source expression".to_string());
+    }
+    let mut kinds: Vec<&str> = Vec::new();
+    for entry in &config.sources {
+        let kind = match &entry.source {
+            Source::Playlist { .. } | Source::LikedSongs => continue,
+            Source::Artist { .. } => "artist",
+            Source::DynamicConfig { .. } => "dynamic playlist",
+            Source::SavedAlbums => "saved albums",
+            Source::TopTracks { .. } => "top tracks",
+            Source::RecentlyPlayed => "recently played",
+        };
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    if kinds.is_empty() {
+        None
+    } else {
+        Some(format!("uses {} sources", kinds.join(", ")))
+    }
+}
+
+impl BundleConfig {
+    fn from_config(config: &DynamicPlaylistConfig, names: &HashMap<String, String>) -> Self {
+        let sources = config
+            .sources
+            .iter()
+            .filter_map(|entry| match &entry.source {
+                Source::Playlist { id } => Some(ServerSource {
+                    kind: "playlist".to_string(),
+                    id: Some(id.clone()),
+                }),
+                Source::LikedSongs => Some(ServerSource {
+                    kind: "likedSongs".to_string(),
+                    id: None,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        // Closest server mode for the ones it lacks
+        let update_mode = match config.update_mode {
+            UpdateMode::Replace => "replace",
+            UpdateMode::Merge | UpdateMode::Sync => "merge",
+            UpdateMode::Append | UpdateMode::Rolling => "append",
+        };
+
+        Self {
+            id: Some(config.id.clone()),
+            name: config.name.clone(),
+            target_playlist_id: config.target_playlist_id.clone(),
+            target_playlist_name: names
+                .get(&config.target_playlist_id)
+                .cloned()
+                .unwrap_or_default(),
+            sources,
+            filters: ServerFilters {
+                exclude_liked: config.filters.exclude_liked,
+                keyword_blacklist: config.filters.keyword_blacklist.clone(),
+            },
+            update_mode: update_mode.to_string(),
+            sample_per_source: config.sample_per_source,
+            include_liked_songs: config.include_liked_songs,
+            processing: ServerProcessing {
+                apply_sort: config.processing.apply_sort,
+                apply_dupes: config.processing.apply_dupes,
+                apply_versions: config.processing.apply_versions,
+                sort_rules: config
+                    .processing
+                    .sort_rules
+                    .iter()
+                    .map(|r| ServerSortRule {
+                        criteria: r.criteria.clone(),
+                        descending: r.descending,
+                    })
+                    .collect(),
+                dupe_preference: config.processing.dupe_preference.clone(),
+                version_preference: config.processing.version_preference.clone(),
+            },
+            enabled: server_unsupported(config).is_none(),
+            desktop: Some(config.clone()),
+        }
+    }
+
+    /// The desktop config, rebuilt from the server fields if the bundle
+    /// came from the server
+    pub fn to_config(&self) -> Result<DynamicPlaylistConfig, String> {
+        if let Some(config) = &self.desktop {
+            return Ok(config.clone());
+        }
+
+        let mut config =
+            DynamicPlaylistConfig::new(self.name.clone(), self.target_playlist_id.clone());
+        if let Some(id) = &self.id {
+            config.id = id.clone();
+        }
+        for source in &self.sources {
+            let source = match (source.kind.as_str(), &source.id) {
+                ("playlist", Some(id)) => Source::Playlist { id: id.clone() },
+                ("likedSongs", _) => Source::LikedSongs,
+                (kind, _) => return Err(format!("Unsupported source '{}'", kind)),
+            };
+            config.sources.push(SourceEntry::from(source));
+        }
+        config.filters = FilterConfig {
+            exclude_liked: self.filters.exclude_liked,
+            keyword_blacklist: self.filters.keyword_blacklist.clone(),
+            ..FilterConfig::default()
+        };
+        config.update_mode = match self.update_mode.as_str() {
+            "replace" => UpdateMode::Replace,
+            "merge" => UpdateMode::Merge,
+            "append" => UpdateMode::Append,
+            other => return Err(format!("Unsupported update mode '{}'", other)),
+        };
+        config.sample_per_source = self.sample_per_source;
+        config.include_liked_songs = self.include_liked_songs;
+        config.processing = ProcessingOptions {
+            apply_sort: self.processing.apply_sort,
+            apply_dupes: self.processing.apply_dupes,
+            apply_versions: self.processing.apply_versions,
+            sort_rules: self
+                .processing
+                .sort_rules
+                .iter()
+                .enumerate()
+                .map(|(i, r)| SortRule {
+                    id: i.to_string(),
+                    criteria: r.criteria.clone(),
+                    descending: r.descending,
+                })
+                .collect(),
+            dupe_preference: self.processing.dupe_preference.clone(),
+            version_preference: self.processing.version_preference.clone(),
+        };
+        Ok(config)
+    }
+}
+
+/// Bundle configs with their schedules. `playlists` names the playlists
+/// they use, so they can be found by name on another account. Configs the
+/// server can't run are exported disabled, with their schedules off.
+pub fn build_bundle(
+    configs: &[DynamicPlaylistConfig],
+    schedules: &[DesktopSchedule],
+    playlists: &[Playlist],
+) -> ConfigBundle {
+    let mut playlist_names = HashMap::new();
+    for config in configs {
+        let mut ids = vec![&config.target_playlist_id];
+        ids.extend(config.all_sources().into_iter().filter_map(|s| match s {
+            Source::Playlist { id } => Some(id),
+            _ => None,
+        }));
+        for id in ids {
+            if let Some(playlist) = playlists.iter().find(|p| &p.id == id) {
+                playlist_names.insert(id.clone(), playlist.name.clone());
+            }
+        }
+    }
+
+    let mut paused_for_server = Vec::new();
+    let schedules = schedules
+        .iter()
+        .filter_map(|s| {
+            let config = configs.iter().find(|c| c.id == s.config_id)?;
+            let pause = s.enabled && server_unsupported(config).is_some();
+            if pause {
+                paused_for_server.push(s.id.clone());
+            }
+            Some(DesktopSchedule {
+                last_run: None,
+                enabled: s.enabled && !pause,
+                ..s.clone()
+            })
+        })
+        .collect();
+
+    ConfigBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: Some(chrono::Local::now().to_rfc3339()),
+        configs: configs
+            .iter()
+            .map(|c| BundleConfig::from_config(c, &playlist_names))
+            .collect(),
+        schedules,
+        playlist_names,
+        paused_for_server,
+    }
+}
+
+/// Read a bundle, a list of server configs or a single server config
+pub fn parse_bundle(content: &str) -> Result<ConfigBundle, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Not valid JSON: {}", e))?;
+
+    let bundle = if value.get("configs").is_some() {
+        serde_json::from_value::<ConfigBundle>(value)
+    } else {
+        let configs = match value {
+            serde_json::Value::Array(_) => serde_json::from_value(value),
+            _ => serde_json::from_value(value).map(|config| vec![config]),
+        };
+        configs.map(|configs| ConfigBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            version: BUNDLE_VERSION,
+            exported_at: None,
+            configs,
+            schedules: Vec::new(),
+            playlist_names: HashMap::new(),
+            paused_for_server: Vec::new(),
+        })
+    }
+    .map_err(|e| format!("Not a config bundle: {}", e))?;
+
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "Bundle was made by a newer version of the app (version {})",
+            bundle.version
+        ));
+    }
+    Ok(bundle)
+}
+
+/// ID of the playlist to use for one from a bundle: the same ID if this
+/// account has it, else one with the same name (ignoring case)
+pub fn find_playlist(
+    playlists: &[Playlist],
+    id: &str,
+    name: Option<&str>,
+    editable_only: bool,
+) -> Option<String> {
+    let usable = |p: &&Playlist| !editable_only || p.editable;
+    playlists
+        .iter()
+        .filter(usable)
+        .find(|p| p.id == id)
+        .or_else(|| {
+            let name = name?.trim();
+            playlists
+                .iter()
+                .filter(usable)
+                .find(|p| p.name.trim().eq_ignore_ascii_case(name))
+        })
+        .map(|p| p.id.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_configs_parse_alone_or_in_a_list() {
+        let config = r#"{
+            "name": "Mix",
+            "target_playlist_id": "target",
+            "sources": [{"type": "playlist", "id": "source"}, {"type": "likedSongs"}],
+            "update_mode": "merge"
+        }"#;
+        let single = parse_bundle(config).unwrap();
+        assert_eq!(single.configs.len(), 1);
+        let list = parse_bundle(&format!("[{}, {}]", config, config)).unwrap();
+        assert_eq!(list.configs.len(), 2);
+
+        let desktop = single.configs[0].to_config().unwrap();
+        assert_eq!(desktop.name, "Mix");
+        assert_eq!(desktop.sources.len(), 2);
+        assert!(matches!(desktop.update_mode, UpdateMode::Merge));
+    }
+
+    #[test]
+    fn exported_bundles_parse_back() {
+        let config = DynamicPlaylistConfig::new("Mix".to_string(), "target".to_string());
+        let json =
+            serde_json::to_string(&build_bundle(std::slice::from_ref(&config), &[], &[])).unwrap();
+        let bundle = parse_bundle(&json).unwrap();
+        assert_eq!(bundle.format, BUNDLE_FORMAT);
+        assert_eq!(bundle.configs[0].to_config().unwrap().id, config.id);
+    }
+
+    #[test]
+    fn configs_the_server_cant_run_are_exported_disabled() {
+        let mut config = DynamicPlaylistConfig::new("Radar".to_string(), "target".to_string());
+        config
+            .sources
+            .push(SourceEntry::from(Source::RecentlyPlayed));
+        let schedule = DesktopSchedule {
+            id: "schedule".to_string(),
+            config_id: config.id.clone(),
+            cron_expression: "0 8 * * *".to_string(),
+            enabled: true,
+            last_run: None,
+        };
+        let bundle = build_bundle(&[config], &[schedule], &[]);
+        assert!(!bundle.configs[0].enabled);
+        assert!(bundle.configs[0].sources.is_empty());
+        assert!(!bundle.schedules[0].enabled);
+        assert_eq!(bundle.paused_for_server, vec!["schedule"]);
+    }
+
+    #[test]
+    fn bad_bundles_are_rejected() {
+        assert!(parse_bundle("not json").is_err());
+        assert!(parse_bundle(r#"{"name": "No target"}"#).is_err());
+        assert!(parse_bundle(r#"{"version": 99, "configs": []}"#).is_err());
+        let unsupported = r#"{"name": "Mix", "target_playlist_id": "t", "update_mode": "shuffle"}"#;
+        assert!(parse_bundle(unsupported).unwrap().configs[0]
+            .to_config()
+            .is_err());
+    }
+}
@@ -1,11 +1,12 @@
 use crate::logic::{remove_duplicates, sort_tracks, AppTrack, ProcessingResult, SortRule};
 use crate::spotify::{
-    do_spotify_auth, fetch_all_playlists, fetch_playlist_tracks, Playlist, SpotifyState,
+    do_spotify_auth, fetch_all_playlists, fetch_playlist_tracks, parse_playlist_id, AuthFlow,
+    OutputTarget, Playlist, SpotifyClient, SpotifyState, DEFAULT_CALLBACK_PORT,
 };
+use crate::vault::{self, CredentialsKey, KeySource, Opened};
 use crate::AppState;
 use rspotify::model::{PlayableItem, PlaylistId};
 use rspotify::prelude::*;
-use rspotify::{AuthCodeSpotify, Credentials, OAuth};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -16,7 +17,10 @@ use tauri::State;
 #[derive(Serialize)]
 pub struct AuthCheckResult {
     authenticated: bool,
+    locked: bool, // Saved credentials need a passphrase (see unlock_credentials)
     playlists: Option<Vec<Playlist>>,
+    #[serde(rename = "missingScopes")]
+    missing_scopes: Vec<String>, // Granted before the app needed them; log in again to fix
 }
 
 #[derive(Serialize)]
@@ -47,15 +51,73 @@ pub struct AutomationConfig {
 #[derive(Serialize, Deserialize)]
 struct SavedCredentials {
     client_id: String,
-    client_secret: String,
+    #[serde(default)]
+    client_secret: Option<String>, // None for PKCE sessions
     refresh_token: Option<String>,
+    #[serde(default)]
+    auth_flow: Option<AuthFlow>, // Missing in files written before PKCE support
+    #[serde(default)]
+    callback_port: Option<u16>,
+    #[serde(default)]
+    token: Option<rspotify::Token>, // Last access token, with expiry and granted scopes
+}
+
+impl SavedCredentials {
+    fn update_token(&mut self, token: &rspotify::Token) {
+        // PKCE refresh tokens are single-use, so the rotated one must be kept
+        if token.refresh_token.is_some() {
+            self.refresh_token = token.refresh_token.clone();
+        }
+        self.token = Some(token.clone());
+    }
+
+    fn flow(&self) -> AuthFlow {
+        match self.client_secret.as_deref() {
+            Some(secret) if !secret.trim().is_empty() => AuthFlow::AuthCode,
+            _ => AuthFlow::Pkce,
+        }
+    }
+}
+
+/// Load saved credentials, upgrading files from older versions in place.
+/// Plain JSON files are encrypted with the machine key on first load.
+fn load_saved_credentials(
+    key: Option<&CredentialsKey>,
+) -> Result<Opened<SavedCredentials>, String> {
+    let (mut creds, key, mut migrated) = match vault::open(&get_credentials_path(), key)? {
+        Opened::Unlocked(creds, key) => (creds, key, false),
+        Opened::Legacy(creds) => (creds, CredentialsKey::machine()?, true),
+        other => return Ok(other),
+    };
+
+    if creds.auth_flow.is_none() {
+        // Old files always carried a secret; a blank one can only mean PKCE
+        creds.auth_flow = Some(creds.flow());
+        if creds.flow() == AuthFlow::Pkce {
+            creds.client_secret = None;
+        }
+        migrated = true;
+    }
+
+    if migrated {
+        save_credentials(&creds, &key);
+        println!("Migrated saved credentials ({:?})", creds.auth_flow);
+    }
+
+    Ok(Opened::Unlocked(creds, key))
+}
+
+fn save_credentials(creds: &SavedCredentials, key: &CredentialsKey) {
+    let creds_path = get_credentials_path();
+    match vault::seal(&creds_path, creds, key) {
+        Ok(_) => println!("Credentials saved to {:?}", creds_path),
+        Err(e) => println!("Failed to save credentials: {}", e),
+    }
 }
 
+/// Data dir of the current profile
 pub fn get_app_data_dir() -> PathBuf {
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("Spotify Sorter");
-    fs::create_dir_all(&path).ok();
-    path
+    crate::profiles::current_profile_dir()
 }
 
 pub fn get_backup_dir() -> PathBuf {
@@ -72,6 +134,26 @@ pub fn get_exports_dir() -> PathBuf {
     path
 }
 
+/// Files and folders of a profile that reveal account data, deleted by
+/// `wipe_secrets`. Anything new that caches or logs account data belongs here.
+const ACCOUNT_DATA_FILES: &[&str] = &[
+    "playlists_cache.json",
+    "spotify_cache.json",
+    "liked_cache.json",
+    "saved_albums_cache.json",
+    "top_tracks_cache.json",
+    "recently_played_cache.json",
+    "version_search_cache.json",
+    "release_tracker.json",
+    "track_additions.json",
+    "ban_list.json",
+    "ignored_tracks.json",
+    "history.json",
+    "forks.json",
+    "backups",
+    "exports",
+];
+
 fn get_credentials_path() -> PathBuf {
     let mut path = get_app_data_dir();
     path.push("credentials.json");
@@ -90,6 +172,20 @@ fn get_ignored_tracks_path() -> PathBuf {
     path
 }
 
+const IGNORED_TRACKS_FILE: crate::storage::ListFile = crate::storage::ListFile {
+    label: "ignored tracks",
+    migrations: &[rename_rejected_context],
+};
+
+/// Early versions saved `rejectedContext` in snake case
+fn rename_rejected_context(track: &mut serde_json::Value) {
+    if let Some(track) = track.as_object_mut() {
+        if let Some(context) = track.remove("rejected_context") {
+            track.entry("rejectedContext").or_insert(context);
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct HistoryEntry {
@@ -104,6 +200,30 @@ pub struct HistoryEntry {
     pub ignored: Option<Vec<ReviewChange>>,
     #[serde(default)]
     pub dynamic_config_backup: Option<String>,
+    #[serde(default)]
+    pub details_backup: Option<PlaylistDetailsBackup>,
+}
+
+/// Playlist details before an edit. Only the edited fields are set.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistDetailsBackup {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub public: Option<bool>,
+    pub collaborative: Option<bool>,
+    pub cover_file: Option<String>, // JPEG in the backups folder
+}
+
+impl PlaylistDetailsBackup {
+    /// Whether there's nothing to restore, e.g. a cover change without the old cover
+    fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.description.is_none()
+            && self.public.is_none()
+            && self.collaborative.is_none()
+            && self.cover_file.is_none()
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -122,105 +242,322 @@ pub struct IgnoredTrack {
     ignored_year: Option<String>,
 
     source_playlist: String,
-    #[serde(alias = "rejected_context")]
     rejected_context: String,
 }
 
 #[tauri::command]
-pub async fn check_auth(state: State<'_, AppState>) -> Result<AuthCheckResult, String> {
+pub async fn check_auth(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AuthCheckResult, String> {
     // Check if we're already in memory
-    {
+    let session = {
         let spotify = state.spotify.lock().unwrap();
-        if spotify.is_authenticated() {
+        spotify
+            .client
+            .clone()
+            .map(|client| (client, spotify.playlists.clone()))
+    };
+    if let Some((client, playlists)) = session {
+        return Ok(AuthCheckResult {
+            authenticated: true,
+            locked: false,
+            playlists: Some(playlists),
+            missing_scopes: client.missing_scopes().await,
+        });
+    }
+
+    // Try to load from disk
+    let key = state.spotify.lock().unwrap().credentials_key.clone();
+    match load_saved_credentials(key.as_ref()) {
+        Ok(Opened::Locked) => {
+            println!("Saved credentials are locked, waiting for passphrase");
             return Ok(AuthCheckResult {
-                authenticated: true,
-                playlists: Some(spotify.playlists.clone()),
+                authenticated: false,
+                locked: true,
+                playlists: None,
+                missing_scopes: Vec::new(),
             });
         }
+        Ok(Opened::Unlocked(creds, key)) => {
+            if let Some(result) = restore_session(&app, &state, creds, key).await {
+                return Ok(result);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => println!("Failed to load saved credentials: {}", e),
     }
 
-    // Try to load from disk
-    let creds_path = get_credentials_path();
-    if creds_path.exists() {
-        if let Ok(content) = fs::read_to_string(&creds_path) {
-            if let Ok(creds) = serde_json::from_str::<SavedCredentials>(&content) {
-                if let Some(refresh_token) = creds.refresh_token {
-                    // Re-authenticate using refresh token
-                    println!("Found saved credentials, refreshing token...");
-
-                    let credentials = Credentials::new(&creds.client_id, &creds.client_secret);
-                    let oauth = OAuth {
-                        redirect_uri: crate::spotify::REDIRECT_URI.to_string(),
-                        scopes: SpotifyState::get_scopes(),
-                        ..Default::default()
-                    };
+    Ok(AuthCheckResult {
+        authenticated: false,
+        locked: false,
+        playlists: None,
+        missing_scopes: Vec::new(),
+    })
+}
 
-                    let client = AuthCodeSpotify::new(credentials, oauth);
-
-                    // Manually set the refresh token and request a new access token
-                    // Note: rspotify doesn't expose a clean way to just inject a refresh token without a Token struct
-                    // So we construct a dummy Token with the refresh token and let it refresh
-                    let token = rspotify::Token {
-                        access_token: "".to_string(), // Will be refreshed
-                        refresh_token: Some(refresh_token.clone()),
-                        expires_in: chrono::Duration::seconds(0),
-                        expires_at: Some(chrono::Utc::now()),
-                        scopes: SpotifyState::get_scopes(),
-                    };
+/// Restore a session from saved credentials using the refresh token
+async fn restore_session(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    mut creds: SavedCredentials,
+    key: CredentialsKey,
+) -> Option<AuthCheckResult> {
+    // Keep the key even if the refresh fails, so the user isn't asked again
+    state.spotify.lock().unwrap().credentials_key = Some(key.clone());
+
+    let profile_id = crate::profiles::current_profile_id();
+    let client = match restore_client(app, &profile_id, &mut creds, &key).await {
+        Ok(client) => client,
+        Err(e) => {
+            println!("{}", e);
+            return None;
+        }
+    };
 
-                    *client.token.lock().await.unwrap() = Some(token);
-
-                    match client.refresh_token().await {
-                        Ok(_) => {
-                            // Success! Fetch user and playlists
-                            match client.current_user().await {
-                                Ok(user) => {
-                                    let user_id = user.id.to_string();
-                                    match fetch_all_playlists(&client, &user_id).await {
-                                        Ok(playlists) => {
-                                            let mut spotify = state.spotify.lock().unwrap();
-                                            spotify.client_id = Some(creds.client_id);
-                                            spotify.client_secret = Some(creds.client_secret);
-                                            spotify.refresh_token = Some(refresh_token);
-                                            spotify.user_id = Some(user_id);
-                                            spotify.playlists = playlists.clone();
-                                            spotify.client = Some(client);
-
-                                            println!("Successfully restored session!");
-                                            return Ok(AuthCheckResult {
-                                                authenticated: true,
-                                                playlists: Some(playlists),
-                                            });
-                                        }
-                                        Err(e) => println!("Failed to fetch playlists: {}", e),
-                                    }
-                                }
-                                Err(e) => println!("Failed to get user: {}", e),
-                            }
-                        }
-                        Err(e) => println!("Failed to refresh token: {}", e),
-                    }
+    let missing_scopes = client.missing_scopes().await;
+    if !missing_scopes.is_empty() {
+        println!("Session lacks scopes: {}", missing_scopes.join(", "));
+    }
+
+    // Success! Fetch user and playlists
+    match client.current_user().await {
+        Ok(user) => {
+            let user_id = user.id.to_string();
+            match fetch_all_playlists(&client, &user_id).await {
+                Ok(playlists) => {
+                    let mut spotify = state.spotify.lock().unwrap();
+                    spotify.client_id = Some(creds.client_id);
+                    spotify.client_secret = creds.client_secret;
+                    spotify.access_token = creds.token.map(|t| t.access_token);
+                    spotify.refresh_token = creds.refresh_token;
+                    spotify.user_id = Some(user_id);
+                    spotify.playlists = playlists.clone();
+                    spotify.client = Some(client);
+
+                    println!("Successfully restored session!");
+                    return Some(AuthCheckResult {
+                        authenticated: true,
+                        locked: false,
+                        playlists: Some(playlists),
+                        missing_scopes,
+                    });
                 }
+                Err(e) => println!("Failed to fetch playlists: {}", e),
             }
         }
+        Err(e) => println!("Failed to get user: {}", e),
     }
 
-    Ok(AuthCheckResult {
-        authenticated: false,
-        playlists: None,
+    None
+}
+
+/// Build a client from saved credentials, refreshing the access token if it expired
+async fn restore_client(
+    app: &tauri::AppHandle,
+    profile_id: &str,
+    creds: &mut SavedCredentials,
+    key: &CredentialsKey,
+) -> Result<SpotifyClient, String> {
+    let refresh_token = creds
+        .refresh_token
+        .clone()
+        .ok_or("No refresh token saved")?;
+
+    let mut client = SpotifyClient::new(
+        &creds.client_id,
+        creds.client_secret.as_deref(),
+        creds.callback_port.unwrap_or(DEFAULT_CALLBACK_PORT),
+    );
+
+    // Reuse the saved access token while it's still valid
+    if let Some(token) = creds.token.clone().filter(|t| !t.is_expired()) {
+        *client.get_token().lock().await.unwrap() = Some(token);
+    } else {
+        // Re-authenticate using refresh token
+        println!("Found saved credentials, refreshing token...");
+
+        // Manually set the refresh token and request a new access token
+        // Note: rspotify doesn't expose a clean way to just inject a refresh token without a Token struct
+        // So we construct a dummy Token with the refresh token and let it refresh
+        let token = rspotify::Token {
+            access_token: "".to_string(), // Will be refreshed
+            refresh_token: Some(refresh_token),
+            expires_in: chrono::Duration::seconds(0),
+            expires_at: Some(chrono::Utc::now()),
+            scopes: SpotifyState::get_scopes(),
+        };
+
+        *client.get_token().lock().await.unwrap() = Some(token);
+
+        client
+            .refresh_token()
+            .await
+            .map_err(|e| format!("Failed to refresh token: {}", e))?;
+
+        let token = client.get_token().lock().await.unwrap().clone();
+        if let Some(token) = token {
+            creds.update_token(&token);
+            save_credentials(creds, key);
+        }
+    }
+
+    // Later refreshes happen inside rspotify, so persist them through the hook
+    client.set_token_hook(token_hook(app, profile_id));
+
+    Ok(client)
+}
+
+/// Persist every token a client of `profile_id` receives
+fn token_hook(app: &tauri::AppHandle, profile_id: &str) -> crate::spotify::TokenHook {
+    let app = app.clone();
+    let profile_id = profile_id.to_string();
+    std::sync::Arc::new(move |token| store_refreshed_token(&app, &profile_id, token))
+}
+
+/// Update the profile's session and saved credentials with a new token
+fn store_refreshed_token(app: &tauri::AppHandle, profile_id: &str, token: &rspotify::Token) {
+    use tauri::Manager;
+
+    let state = app.state::<AppState>();
+    let key = {
+        let is_active = crate::profiles::load_profiles().active == profile_id;
+        let mut sessions = state.sessions.lock().unwrap();
+        let mut spotify = state.spotify.lock().unwrap();
+        let session = if is_active {
+            Some(&mut *spotify)
+        } else {
+            sessions.get_mut(profile_id)
+        };
+
+        match session {
+            Some(session) => {
+                session.access_token = Some(token.access_token.clone());
+                if token.refresh_token.is_some() {
+                    session.refresh_token = token.refresh_token.clone();
+                }
+                session.credentials_key.clone()
+            }
+            None => None,
+        }
+    };
+
+    // Without a key the credentials haven't been saved (or unlocked) yet
+    if let Some(key) = key {
+        crate::profiles::sync_with_profile(profile_id, || {
+            match load_saved_credentials(Some(&key)) {
+                Ok(Opened::Unlocked(mut creds, key)) => {
+                    creds.update_token(token);
+                    save_credentials(&creds, &key);
+                }
+                Ok(_) => {}
+                Err(e) => println!("Failed to save refreshed token: {}", e),
+            }
+        });
+    }
+}
+
+/// Fail before touching any playlist if the session lacks a required scope
+async fn require_scopes(client: &SpotifyClient) -> Result<(), String> {
+    require_scopes_of(client, &SpotifyState::playlist_scopes()).await
+}
+
+async fn require_scopes_of(
+    client: &SpotifyClient,
+    needed: &std::collections::HashSet<String>,
+) -> Result<(), String> {
+    let missing = client.missing_scopes_of(needed).await;
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(format!(
+        "Your Spotify login is missing permissions this feature needs ({}). Please log out and connect again.",
+        missing.join(", ")
+    ))
+}
+
+/// Unlock passphrase-protected credentials and restore the session
+#[tauri::command]
+pub async fn unlock_credentials(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<AuthCheckResult, String> {
+    let (creds, key) = vault::unlock::<SavedCredentials>(&get_credentials_path(), &passphrase)?;
+
+    Ok(restore_session(&app, &state, creds, key)
+        .await
+        .unwrap_or(AuthCheckResult {
+            authenticated: false,
+            locked: false,
+            playlists: None,
+            missing_scopes: Vec::new(),
+        }))
+}
+
+/// Re-encrypt saved credentials with a new key. An empty passphrase switches
+/// to the machine-bound key.
+#[tauri::command]
+pub fn rotate_credentials_key(
+    state: State<AppState>,
+    new_passphrase: Option<String>,
+) -> Result<String, String> {
+    let key = state.spotify.lock().unwrap().credentials_key.clone();
+    let creds = match load_saved_credentials(key.as_ref())? {
+        Opened::Unlocked(creds, _) => creds,
+        Opened::Locked => return Err("Credentials are locked. Unlock them first.".to_string()),
+        _ => return Err("No saved credentials".to_string()),
+    };
+
+    let new_key = match new_passphrase.filter(|p| !p.is_empty()) {
+        Some(passphrase) => CredentialsKey::passphrase(&passphrase)?,
+        None => CredentialsKey::machine()?,
+    };
+
+    vault::seal(&get_credentials_path(), &creds, &new_key)?;
+    let source = new_key.source();
+    state.spotify.lock().unwrap().credentials_key = Some(new_key);
+
+    Ok(match source {
+        KeySource::Passphrase => "Credentials are now protected by your passphrase".to_string(),
+        KeySource::Machine => "Credentials are now bound to this machine".to_string(),
     })
 }
 
 #[tauri::command]
 pub async fn initialize_spotify(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
     client_id: String,
-    client_secret: String,
+    client_secret: Option<String>,
+    callback_port: Option<u16>,
 ) -> Result<InitResult, String> {
-    println!("Starting Spotify OAuth flow...");
+    // A blank secret from the setup form means the user picked PKCE
+    let client_secret = client_secret.filter(|s| !s.trim().is_empty());
+    let callback_port = callback_port.unwrap_or(DEFAULT_CALLBACK_PORT);
+    if callback_port < 1024 {
+        return Err("Callback port must be between 1024 and 65535".to_string());
+    }
+
+    // Starting a new attempt drops the sender of the previous one, which cancels it
+    let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
+    *state.auth_cancel.lock().unwrap() = Some(cancel_tx);
+    println!(
+        "Starting Spotify OAuth flow ({})...",
+        if client_secret.is_some() {
+            "Authorization Code"
+        } else {
+            "PKCE"
+        }
+    );
 
-    let (client, user_id, playlists) =
-        do_spotify_auth(client_id.clone(), client_secret.clone()).await?;
+    let (mut client, user_id, playlists) = do_spotify_auth(
+        client_id.clone(),
+        client_secret.clone(),
+        callback_port,
+        cancel_rx,
+    )
+    .await?;
 
     println!(
         "Successfully authenticated! Found {} playlists.",
@@ -228,36 +565,41 @@ pub async fn initialize_spotify(
     );
 
     // Save credentials
-    let refresh_token = client
-        .get_token()
-        .lock()
-        .await
-        .unwrap()
-        .as_ref()
-        .and_then(|t| t.refresh_token.clone());
+    let token = client.get_token().lock().await.unwrap().clone();
+    let refresh_token = token.as_ref().and_then(|t| t.refresh_token.clone());
 
-    if let Some(rt) = &refresh_token {
-        let creds = SavedCredentials {
-            client_id: client_id.clone(),
-            client_secret: client_secret.clone(),
-            refresh_token: Some(rt.clone()),
-        };
+    // Reuse an unlocked passphrase key, otherwise bind to this machine
+    let key = match state.spotify.lock().unwrap().credentials_key.clone() {
+        Some(key) => key,
+        None => CredentialsKey::machine()?,
+    };
 
-        let creds_path = get_credentials_path();
-        if let Ok(json) = serde_json::to_string_pretty(&creds) {
-            fs::write(&creds_path, json).ok();
-            println!("Credentials saved to {:?}", creds_path);
-        }
+    if let Some(rt) = &refresh_token {
+        save_credentials(
+            &SavedCredentials {
+                client_id: client_id.clone(),
+                client_secret: client_secret.clone(),
+                refresh_token: Some(rt.clone()),
+                auth_flow: Some(client.auth_flow()),
+                callback_port: Some(callback_port),
+                token: token.clone(),
+            },
+            &key,
+        );
     }
 
+    client.set_token_hook(token_hook(&app, &crate::profiles::current_profile_id()));
+
     {
         let mut spotify = state.spotify.lock().unwrap();
         spotify.client_id = Some(client_id);
-        spotify.client_secret = Some(client_secret);
+        spotify.client_secret = client_secret;
+        spotify.access_token = token.map(|t| t.access_token);
         spotify.refresh_token = refresh_token;
         spotify.user_id = Some(user_id);
         spotify.playlists = playlists.clone();
         spotify.client = Some(client);
+        spotify.credentials_key = Some(key);
     }
 
     Ok(InitResult {
@@ -267,6 +609,14 @@ pub async fn initialize_spotify(
     })
 }
 
+/// Stop waiting for the browser in a running `initialize_spotify`
+#[tauri::command]
+pub fn cancel_auth(state: State<AppState>) {
+    if let Some(cancel) = state.auth_cancel.lock().unwrap().take() {
+        cancel.send(()).ok();
+    }
+}
+
 #[tauri::command]
 pub fn logout(state: State<AppState>) {
     let mut spotify = state.spotify.lock().unwrap();
@@ -277,6 +627,7 @@ pub fn logout(state: State<AppState>) {
     spotify.user_id = None;
     spotify.playlists = Vec::new();
     spotify.client = None;
+    spotify.credentials_key = None;
 
     // Remove saved credentials
     let creds_path = get_credentials_path();
@@ -285,6 +636,34 @@ pub fn logout(state: State<AppState>) {
     }
 }
 
+/// Log out and delete everything of the current profile that could reveal
+/// the account: credentials, the machine key, caches, history and backups
+#[tauri::command]
+pub fn wipe_secrets(state: State<AppState>) -> Result<(), String> {
+    logout(state);
+
+    // The machine key is shared, so only drop it when no other profile may need it
+    if crate::profiles::load_profiles().profiles.len() == 1 {
+        vault::wipe_machine_key();
+    }
+
+    let data_dir = get_app_data_dir();
+    for name in ACCOUNT_DATA_FILES {
+        let path = data_dir.join(name);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else if path.exists() {
+            fs::remove_file(&path)
+        } else {
+            Ok(())
+        };
+        removed.map_err(|e| format!("Failed to delete {}: {}", name, e))?;
+    }
+
+    println!("Wiped credentials and account data");
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ReviewChange {
     pub id: String,
@@ -327,6 +706,8 @@ pub struct ScanResult {
     pub name: String,
     pub changes: Vec<ReviewChange>,
     pub stats: ProcessingResult,
+    /// Upstream changes of a fork, pulled in when the changes are applied
+    pub fork_sync: Option<crate::forks::ForkSyncSummary>,
 }
 
 #[tauri::command]
@@ -344,12 +725,18 @@ pub async fn scan_playlist(
     println!("=== SCAN PLAYLIST ===");
     println!("Processing {} playlists", playlist_ids.len());
 
+    let playlist_ids = playlist_ids
+        .iter()
+        .map(|input| parse_playlist_id(input))
+        .collect::<Result<Vec<_>, _>>()?;
+
     // Get the Spotify client
     let client = {
         let spotify = state.spotify.lock().unwrap();
         spotify.client.clone()
     }
     .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
 
     let mut scan_results: Vec<ScanResult> = Vec::new();
 
@@ -369,6 +756,24 @@ pub async fn scan_playlist(
         let original_count = tracks.len();
         println!("  Fetched {} tracks (Name: {})", original_count, pl_name);
 
+        // Forks pull upstream changes first, then get processed like any
+        // playlist. Nothing is written until the changes are applied.
+        let mut fork_sync = None;
+        if let Some(link) = crate::forks::find_fork(playlist_id) {
+            match crate::forks::check_fork(&client, &link, &app).await {
+                Ok(Some(sync)) => {
+                    let summary = sync.apply(&mut tracks);
+                    println!(
+                        "  Fork of {} changed: +{} -{}",
+                        link.upstream_name, summary.added, summary.removed
+                    );
+                    fork_sync = Some(summary);
+                }
+                Ok(None) => {}
+                Err(e) => println!("  Failed to check fork: {}", e),
+            }
+        }
+
         let mut changes: Vec<ReviewChange> = Vec::new();
         let mut duplicates_count = 0;
 
@@ -407,371 +812,56 @@ pub async fn scan_playlist(
                 _version_preference
             );
 
-            // limiting to avoids rate limits, but let's try sequential for safety first
+            let mut search_cache = crate::versions::SearchCache::load();
+            let bans = crate::bans::load_ban_list();
             for (idx, track) in tracks.iter_mut().enumerate() {
-                // Skip if this track was already marked for duplicate removal (not in this list, as we deduped tracks vec already)
-
-                // Get all artists from the track
-                let all_artists: Vec<&str> = track
-                    .artist_names
-                    .split(',')
-                    .map(|a| a.trim())
-                    .filter(|a| !a.is_empty())
-                    .collect();
-
-                let clean_name = clean_title(&track.name);
-
-                debug_info!(
-                    &app,
-                    format!(
-                        "Checking track: '{}' (cleaned: '{}')",
-                        track.name, clean_name
-                    ),
-                    format!("Artists: {:?}", all_artists)
-                );
-
-                // Search for EACH artist to find versions credited differently
-                let mut all_search_results: Vec<AppTrack> = Vec::new();
-
-                for artist in &all_artists {
-                    let query = format!("track:{} artist:{}", clean_name, artist);
-                    debug_search!(&app, format!("Searching: {}", query));
-
-                    match client
-                        .search(
-                            &query,
-                            rspotify::model::SearchType::Track,
-                            None,
-                            None,
-                            Some(10),
-                            None,
-                        )
-                        .await
-                    {
-                        Ok(result) => {
-                            if let rspotify::model::SearchResult::Tracks(page) = result {
-                                debug_info!(
-                                    &app,
-                                    format!(
-                                        "Found {} results for artist '{}'",
-                                        page.items.len(),
-                                        artist
-                                    )
-                                );
-                                for t in &page.items {
-                                    if let Some(app_track) = AppTrack::from_spotify(t) {
-                                        // Avoid duplicates
-                                        if !all_search_results.iter().any(|r| r.id == app_track.id)
-                                        {
-                                            all_search_results.push(app_track);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            debug_error!(&app, format!("Search failed for '{}': {}", artist, e))
-                        }
-                    }
-                }
-
-                // FALLBACK: Also search without artist filter to catch edge cases
-                let query_no_artist = format!("track:{}", clean_name);
-                debug_search!(
-                    &app,
-                    format!("Fallback search (no artist): {}", query_no_artist)
-                );
-
-                match client
-                    .search(
-                        &query_no_artist,
-                        rspotify::model::SearchType::Track,
-                        None,
-                        None,
-                        Some(10),
-                        None,
-                    )
-                    .await
-                {
-                    Ok(result) => {
-                        if let rspotify::model::SearchResult::Tracks(page) = result {
-                            debug_info!(
-                                &app,
-                                format!("Fallback found {} results", page.items.len())
-                            );
-                            for t in &page.items {
-                                if let Some(app_track) = AppTrack::from_spotify(t) {
-                                    if !all_search_results.iter().any(|r| r.id == app_track.id) {
-                                        all_search_results.push(app_track);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => debug_error!(&app, format!("Fallback search failed: {}", e)),
-                }
-
-                // ADDITIONAL: Search without "Original" suffix to find base album versions
-                let base_name = strip_original_suffix(&clean_name);
-                if base_name != clean_name {
-                    for artist in &all_artists {
-                        let query_base = format!("track:{} artist:{}", base_name, artist);
-                        debug_search!(&app, format!("Base search (no 'Original'): {}", query_base));
-
-                        match client
-                            .search(
-                                &query_base,
-                                rspotify::model::SearchType::Track,
-                                None,
-                                None,
-                                Some(10),
-                                None,
-                            )
-                            .await
-                        {
-                            Ok(result) => {
-                                if let rspotify::model::SearchResult::Tracks(page) = result {
-                                    debug_info!(
-                                        &app,
-                                        format!(
-                                            "Base search found {} results for artist '{}'",
-                                            page.items.len(),
-                                            artist
-                                        )
-                                    );
-                                    for t in &page.items {
-                                        if let Some(app_track) = AppTrack::from_spotify(t) {
-                                            if !all_search_results
-                                                .iter()
-                                                .any(|r| r.id == app_track.id)
-                                            {
-                                                all_search_results.push(app_track);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => debug_error!(
-                                &app,
-                                format!("Base search failed for '{}': {}", artist, e)
-                            ),
-                        }
-                    }
-                }
-
-                debug_info!(
-                    &app,
-                    format!("Total unique results: {}", all_search_results.len())
-                );
-
-                // Now filter and process results
-                if all_search_results.is_empty() {
-                    continue;
-                }
-
-                // Filter candidates from combined search results
-                let original_artists: Vec<&str> =
-                    track.artist_names.split(',').map(|a| a.trim()).collect();
-
-                let mut candidates: Vec<AppTrack> = all_search_results
-                    .into_iter()
-                    .filter(|t| {
-                        // Skip if this is the exact same track (same Spotify ID)
-                        if t.id == track.id {
-                            debug_skipped!(&app, format!("SKIPPED (same ID): {}", t.id));
-                            return false;
-                        }
-
-                        // Title match (relaxed)
-                        let title_match = match_titles_relaxed(&t.name, &track.name);
-                        if !title_match {
-                            debug_rejected!(
-                                &app,
-                                format!("REJECTED (title): '{}'", t.name),
-                                format!(
-                                    "'{}' vs '{}'",
-                                    clean_title(&t.name),
-                                    clean_title(&track.name)
-                                )
-                            );
-                            return false;
-                        }
-
-                        // Artist match: Check if ANY original artist appears in candidate
-                        let candidate_artists: Vec<&str> =
-                            t.artist_names.split(',').map(|a| a.trim()).collect();
-
-                        let artist_match = original_artists.iter().any(|orig| {
-                            candidate_artists
-                                .iter()
-                                .any(|cand| orig.eq_ignore_ascii_case(cand))
-                        });
-
-                        if !artist_match {
-                            debug_rejected!(
-                                &app,
-                                format!("REJECTED (artist): '{}'", t.artist_names),
-                                format!("Expected one of: {:?}", original_artists)
-                            );
-                        } else {
-                            debug_passed!(
-                                &app,
-                                format!("PASSED: '{}'", t.name),
-                                format!(
-                                    "Album: '{}' ({}, {})",
-                                    t.album_name, t.release_date, t.album_type
-                                )
-                            );
-                        }
-
-                        artist_match
-                    })
-                    .collect();
-
-                debug_info!(
+                let best = crate::versions::find_better_version(
+                    &client,
                     &app,
-                    format!("Candidates after filter: {}", candidates.len())
-                );
-
-                if candidates.is_empty() {
-                    continue;
-                }
-
-                // Sort: Primary by date, Secondary by album_type (single > album > compilation)
-                candidates.sort_by(|a, b| {
-                    let date_a = crate::logic::parse_date_obj(&a.release_date);
-                    let date_b = crate::logic::parse_date_obj(&b.release_date);
-
-                    // Primary sort by date
-                    let date_cmp = match _version_preference.as_str() {
-                        "Artist Only: Oldest Version" | "Global: Oldest Version" => {
-                            date_a.cmp(&date_b)
-                        }
-                        "Artist Only: Newest Version" | "Global: Newest Version" => {
-                            date_b.cmp(&date_a)
-                        }
-                        _ => std::cmp::Ordering::Equal,
-                    };
-
-                    // If dates are equal, prefer by album_type: single > album > compilation
-                    if date_cmp == std::cmp::Ordering::Equal {
-                        // Assign priority: single=0, album=1, compilation=2
-                        fn type_priority(t: &str) -> u8 {
-                            match t.to_lowercase().as_str() {
-                                "single" => 0,
-                                "album" => 1,
-                                "compilation" => 2,
-                                _ => 3,
-                            }
-                        }
-                        type_priority(&a.album_type).cmp(&type_priority(&b.album_type))
-                    } else {
-                        date_cmp
-                    }
-                });
-
-                // Log sorted candidates
-                println!("      Sorted candidates ({}):", _version_preference);
-                for (i, c) in candidates.iter().enumerate() {
+                    track,
+                    &_version_preference,
+                    &mut search_cache,
+                    &bans,
+                )
+                .await;
+
+                if let Some(best) = best {
                     println!(
-                        "        [{}] '{}' from '{}' ({}, type: '{}')",
-                        i, c.name, c.album_name, c.release_date, c.album_type
-                    );
-                }
-
-                if let Some(best) = candidates.first() {
-                    // Compare with current
-                    let current_date = crate::logic::parse_date_obj(&track.release_date);
-                    let best_date = crate::logic::parse_date_obj(&best.release_date);
-
-                    // Album type priority: single=0, album=1, compilation=2
-                    fn type_priority(t: &str) -> u8 {
-                        match t.to_lowercase().as_str() {
-                            "single" => 0,
-                            "album" => 1,
-                            "compilation" => 2,
-                            _ => 3,
-                        }
-                    }
-                    let current_priority = type_priority(&track.album_type);
-                    let best_priority = type_priority(&best.album_type);
-
-                    debug_comparison!(
-                        &app,
-                        format!("Best: '{}' ({})", best.name, best.release_date),
-                        format!("Type: {}, Priority: {}", best.album_type, best_priority)
-                    );
-                    debug_comparison!(
-                        &app,
-                        format!("Current: '{}' ({})", track.name, track.release_date),
-                        format!("Type: {}, Priority: {}", track.album_type, current_priority)
-                    );
-
-                    debug_comparison!(
-                        &app,
-                        format!(
-                            "Album types: current='{}' (priority {}), best='{}' (priority {})",
-                            track.album_type, current_priority, best.album_type, best_priority
-                        )
+                        "    Found better version for '{}': {} ({}) -> {} ({})",
+                        track.name, track.release_date, track.id, best.release_date, best.id
                     );
 
-                    // NEVER downgrade (e.g. single→album, album→compilation)
-                    if best_priority > current_priority {
-                        debug_skipped!(
-                            &app,
-                            format!(
-                                "SKIPPED: Won't downgrade from {} to {}",
-                                track.album_type, best.album_type
-                            )
-                        );
-                        continue;
-                    }
-
-                    // Replace if: better date OR same date but better album type (upgrade)
-                    let should_replace = match _version_preference.as_str() {
-                        p if p.contains("Oldest") => best_date < current_date,
-                        p if p.contains("Newest") => best_date > current_date,
-                        _ => false,
-                    } || (best_date == current_date
-                        && best_priority < current_priority);
-
-                    println!("      should_replace: {}", should_replace);
-
-                    if should_replace {
-                        println!(
-                            "    Found better version for '{}': {} ({}) -> {} ({})",
-                            track.name, track.release_date, track.id, best.release_date, best.id
-                        );
-
-                        changes.push(ReviewChange {
-                            id: uuid::Uuid::new_v4().to_string(),
-                            change_type: "replace".to_string(),
-                            // New Info
-                            new_title: Some(best.name.clone()),
-                            new_artist: Some(best.artist_names.clone()),
-                            new_album: Some(best.album_name.clone()),
-                            new_date: Some(best.release_date.clone()),
-                            // Current (Old) Info
-                            rem_title: Some(track.name.clone()),
-                            rem_artist: Some(track.artist_names.clone()),
-                            rem_album: Some(track.album_name.clone()),
-                            rem_date: Some(track.release_date.clone()),
-                            // Tech
-                            track_uri: best.uri.clone(), // We want to ADD this one
-                            original_index: idx,         // We want to REPLACE the one at this index
-                            original_uri: track.uri.clone(),
-                        });
+                    changes.push(ReviewChange {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        change_type: "replace".to_string(),
+                        // New Info
+                        new_title: Some(best.name.clone()),
+                        new_artist: Some(best.artist_names.clone()),
+                        new_album: Some(best.album_name.clone()),
+                        new_date: Some(best.release_date.clone()),
+                        // Current (Old) Info
+                        rem_title: Some(track.name.clone()),
+                        rem_artist: Some(track.artist_names.clone()),
+                        rem_album: Some(track.album_name.clone()),
+                        rem_date: Some(track.release_date.clone()),
+                        // Tech
+                        track_uri: best.uri.clone(), // We want to ADD this one
+                        original_index: idx,         // We want to REPLACE the one at this index
+                        original_uri: track.uri.clone(),
+                    });
 
-                        // Update the track in our list so sorting uses the new one
-                        *track = best.clone();
-                        versions_replaced += 1;
-                    }
+                    // Update the track in our list so sorting uses the new one
+                    *track = best;
+                    versions_replaced += 1;
                 }
 
                 // simple rate limit
                 // std::thread::sleep(std::time::Duration::from_millis(50)); // async sleep?
                 // tokio::time::sleep(std::time::Duration::from_millis(50)).await;
             }
+            if let Err(e) = search_cache.save() {
+                println!("  {}", e);
+            }
         }
 
         // 3. Sorting (No individual review changes, just stats)
@@ -790,6 +880,7 @@ pub async fn scan_playlist(
                 duplicates_removed: duplicates_count,
                 versions_replaced,
             },
+            fork_sync,
         });
     }
 
@@ -805,8 +896,11 @@ pub async fn apply_changes(
     rejected_changes: Vec<ReviewChange>,
     sort_rules: Vec<SortRule>,
     sort_enabled: bool,
+    output_target: Option<OutputTarget>,
 ) -> Result<String, String> {
     println!("=== APPLY CHANGES: {} ===", playlist_id);
+    let playlist_id = parse_playlist_id(&playlist_id)?;
+    let output_target = output_target.unwrap_or_default();
 
     // Handle Rejections first (independent of Spotify ops)
     if !rejected_changes.is_empty() {
@@ -814,7 +908,7 @@ pub async fn apply_changes(
             "  Processing {} rejections (adding to ignore list)...",
             rejected_changes.len()
         );
-        let mut ignored = get_ignored_tracks().unwrap_or_default();
+        let mut ignored: Vec<IgnoredTrack> = IGNORED_TRACKS_FILE.load(&get_ignored_tracks_path());
 
         for change in &rejected_changes {
             // We ignore the ORIGINAL track that was targeted
@@ -867,9 +961,9 @@ pub async fn apply_changes(
             }
         }
 
-        if let Ok(json) = serde_json::to_string_pretty(&ignored) {
-            fs::write(get_ignored_tracks_path(), json).ok();
-            println!("  Saved ignored tracks to disk.");
+        match IGNORED_TRACKS_FILE.save(&get_ignored_tracks_path(), &ignored) {
+            Ok(()) => println!("  Saved ignored tracks to disk."),
+            Err(e) => println!("  {}", e),
         }
     }
 
@@ -879,6 +973,7 @@ pub async fn apply_changes(
         spotify.client.clone()
     }
     .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
 
     // 1. Fetch latest tracks (to ensure we work on fresh state)
     let pid =
@@ -895,23 +990,63 @@ pub async fn apply_changes(
         .map_err(|e| format!("Failed to fetch tracks: {}", e))?;
 
     let mut tracks = fetched_tracks;
-    let original_uris: Vec<String> = tracks.iter().map(|t| t.uri.clone()).collect();
 
     println!("  Fetched {} tracks", tracks.len());
 
-    // 2. Create Backup & History Entry (Snapshot of state BEFORE change)
-    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
-    let backup_filename = format!("{}_{}.json", sanitize_filename(&pl_name), timestamp);
+    // 1b. Resolve the playlist to write to. Only the target gets backed up and changed.
+    // A playlist created here is deleted again if the apply fails.
+    let mut created_id = None;
+    let (target_id, target_name, target_tracks) = match &output_target {
+        OutputTarget::InPlace => (playlist_id.clone(), pl_name.clone(), tracks.clone()),
+        OutputTarget::ExistingPlaylist { id } => {
+            let id = parse_playlist_id(id)?;
+            let (name, existing) = crate::spotify::fetch_playlist_tracks(&client, &id, &app)
+                .await
+                .map_err(|e| format!("Failed to fetch target playlist: {}", e))?;
+            (id, name, existing)
+        }
+        OutputTarget::NewPlaylist { name } => {
+            let user_id = state
+                .spotify
+                .lock()
+                .unwrap()
+                .user_id
+                .clone()
+                .ok_or("Not authenticated")?;
+            let name = name
+                .clone()
+                .filter(|n| !n.trim().is_empty())
+                .unwrap_or_else(|| format!("{} (Processed)", pl_name));
+            let description = output_description(&pl_name, &approved_changes, sort_enabled);
+            let created =
+                crate::spotify::create_playlist(&client, &user_id, &name, &description).await?;
+            state
+                .spotify
+                .lock()
+                .unwrap()
+                .playlists
+                .insert(0, created.clone());
+            publish_playlists(&app, &state);
+            println!("  Created output playlist {}", created.id);
+            created_id = Some(created.id.clone());
+            (created.id, created.name, Vec::new())
+        }
+    };
+    let original_uris: Vec<String> = target_tracks.iter().map(|t| t.uri.clone()).collect();
+
+    // 2. Create Backup & History Entry (Snapshot of state BEFORE change)
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let backup_filename = format!("{}_{}.json", sanitize_filename(&target_name), timestamp);
     let backup_path = get_backup_dir().join(&backup_filename);
 
-    let backup_tracks: Vec<serde_json::Value> = tracks
+    let backup_tracks: Vec<serde_json::Value> = target_tracks
         .iter()
         .map(|t| serde_json::to_value(t).unwrap())
         .collect();
 
     let backup_data = serde_json::json!({
-        "playlist_id": playlist_id,
-        "playlist_name": pl_name,
+        "playlist_id": target_id,
+        "playlist_name": target_name,
         "backup_time": timestamp,
         "tracks": backup_tracks
     });
@@ -920,69 +1055,56 @@ pub async fn apply_changes(
         fs::write(&backup_path, json).ok();
     }
 
+    // 2b. Pull upstream changes into a fork edited in place, now that it's backed up
+    let mut fork_sync = None;
+    if target_id == playlist_id {
+        if let Some(link) = crate::forks::find_fork(&playlist_id) {
+            match crate::forks::check_fork(&client, &link, &app).await {
+                Ok(Some(sync)) => {
+                    let summary = sync.apply(&mut tracks);
+                    println!(
+                        "  Pulled fork changes of {}: +{} -{}",
+                        link.upstream_name, summary.added, summary.removed
+                    );
+                    fork_sync = Some((sync, summary));
+                }
+                Ok(None) => {}
+                Err(e) => println!("  Failed to check fork: {}", e),
+            }
+        }
+    }
+
     // Log History
-    let action_desc = format!(
+    let mut action_desc = format!(
         "Applied {} changes ({} ignored)",
         approved_changes.len(),
         rejected_changes.len()
     );
+    if target_id != playlist_id {
+        action_desc.push_str(&format!(" from {}", pl_name));
+    }
+    if let Some((_, summary)) = &fork_sync {
+        action_desc.push_str(&format!(
+            ", synced fork (+{} -{})",
+            summary.added, summary.removed
+        ));
+    }
     let entry = HistoryEntry {
         id: uuid::Uuid::new_v4().to_string(),
-        playlist_name: pl_name.clone(),
-        playlist_id: playlist_id.clone(),
+        playlist_name: target_name.clone(),
+        playlist_id: target_id.clone(),
         action: action_desc,
         time: chrono::Local::now().format("%H:%M:%S").to_string(),
         backup_file: backup_filename,
         changes: Some(approved_changes.clone()),
         ignored: Some(rejected_changes.clone()),
         dynamic_config_backup: None,
+        details_backup: None,
     };
 
-    // LOCK HISTORY ACCESS
-    {
-        let _lock = state.history_lock.lock().unwrap();
-
-        // Safety logic: try to read, if fail, assume empty or error (but we propagate error now)
-        // If read fails, WE DO NOT WRITE, to avoid overwriting with empty list if file is just locked or weird.
-        // Wait, if we have the mutex, we shouldn't have file contention from OUR app.
-        // But if read fails for other reasons, we probably shouldn't blindly overwrite.
-
-        let mut hist = match get_history() {
-            Ok(h) => h,
-            Err(_) => Vec::new(), // If file missing or corrupted, start fresh? Or Error?
-                                  // User wants PERSISTENCE. If read fails, and we write [entry], we lose old history.
-                                  // BUT standard get_history() returns empty Vec if file doesn't exist.
-                                  // If it exists but fails to read (e.g. valid lock but permission error?), we risk data loss.
-                                  // Better to fallback to reading direct file if `get_history` (which is a command) does weird stuff?
-                                  // `get_history` is just a function wrapper now.
-                                  // Let's use the same logic as before but inside the lock.
-        };
-
-        // Actually, let's look at get_history implementation.
-        // It's in commands.rs. It just reads the file.
-        // Re-implementing read here inside lock to be safe/atomic?
-        // Or just trust get_history?
-        // Let's just call get_history but handle the result carefully.
-
-        // Re-read history safely
-        let history_path = get_history_path();
-        if history_path.exists() {
-            match fs::read_to_string(&history_path) {
-                Ok(content) => {
-                    match serde_json::from_str::<Vec<HistoryEntry>>(&content) {
-                        Ok(h) => hist = h,
-                        Err(_) => {} // Corrupt file? Append to new?
-                    }
-                }
-                Err(e) => return Err(format!("Failed to read history file: {}", e)),
-            }
-        }
-
-        hist.push(entry);
-        if let Ok(json) = serde_json::to_string_pretty(&hist) {
-            fs::write(&history_path, json)
-                .map_err(|e| format!("Failed to write history: {}", e))?;
-        }
+    if let Err(e) = append_history(&state, entry) {
+        discard_output_playlist(&app, &state, &client, created_id.as_deref()).await;
+        return Err(e);
     }
 
     // 3. Apply Removals
@@ -1098,23 +1220,42 @@ pub async fn apply_changes(
         println!("  Sorted items successfully.");
     }
 
+    // Local files can only stay in the playlist they're already in
+    if target_id != playlist_id {
+        let before = tracks.len();
+        tracks.retain(|t| !t.uri.starts_with("spotify:local"));
+        if tracks.len() != before {
+            println!(
+                "  Skipping {} local files (can't be copied)",
+                before - tracks.len()
+            );
+        }
+    }
+
     // 5. Update Spotify
     let track_uris: Vec<String> = tracks.iter().map(|t| t.uri.clone()).collect();
-    crate::spotify::update_playlist_items(
+    if let Err(e) = crate::spotify::update_playlist_items(
         &client,
-        &playlist_id,
+        &target_id,
         track_uris,
         Some(original_uris),
         &app,
     )
-    .await?;
+    .await
+    {
+        discard_output_playlist(&app, &state, &client, created_id.as_deref()).await;
+        return Err(e);
+    }
+    if let Some((sync, _)) = fork_sync {
+        if let Err(e) = crate::forks::finish_sync(sync) {
+            println!("  {}", e);
+        }
+    }
 
     // 6. Update Cache with Sorted Tracks (Immediate Reflection)
     // We update the local cache so the UI reflects the changes instantly without a full scan
     {
-        let mut path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-        path.push("Spotify Sorter");
-        path.push("spotify_cache.json");
+        let path = get_app_data_dir().join("spotify_cache.json");
 
         let mut cache = if path.exists() {
             if let Ok(content) = fs::read_to_string(&path) {
@@ -1130,7 +1271,7 @@ pub async fn apply_changes(
         // However, updating the tracks allows the UI to show the correct order immediately.
         // The next scan will fix the snapshot ID.
         cache.insert(
-            playlist_id.clone(),
+            target_id.clone(),
             crate::spotify::PlaylistCacheEntry {
                 snapshot_id: "updated_locally".to_string(),
                 tracks: tracks,
@@ -1140,16 +1281,64 @@ pub async fn apply_changes(
 
         if let Ok(json) = serde_json::to_string(&cache) {
             let _ = fs::write(path, json);
-            println!(
-                "  Updated cache for {} with sorted local state",
-                playlist_id
-            );
+            println!("  Updated cache for {} with sorted local state", target_id);
         }
     }
 
+    if target_id != playlist_id {
+        return Ok(format!("Saved result to {}", target_name));
+    }
     Ok("Playlist updated successfully".to_string())
 }
 
+/// Delete an output playlist created by an apply that then failed
+async fn discard_output_playlist(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    client: &SpotifyClient,
+    playlist_id: Option<&str>,
+) {
+    let Some(playlist_id) = playlist_id else {
+        return;
+    };
+    if let Err(e) = crate::spotify::delete_playlist(client, playlist_id).await {
+        println!("  Couldn't delete output playlist {}: {}", playlist_id, e);
+        return;
+    }
+    state
+        .spotify
+        .lock()
+        .unwrap()
+        .playlists
+        .retain(|p| p.id != playlist_id);
+    publish_playlists(app, state);
+    println!("  Deleted output playlist {}", playlist_id);
+}
+
+/// Description for a playlist created from the processed tracks of another
+fn output_description(source_name: &str, changes: &[ReviewChange], sorted: bool) -> String {
+    let mut steps = Vec::new();
+    if sorted {
+        steps.push("sorted");
+    }
+    if changes.iter().any(|c| c.change_type == "duplicate") {
+        steps.push("duplicates removed");
+    }
+    if changes.iter().any(|c| c.change_type == "replace") {
+        steps.push("versions replaced");
+    }
+
+    let mut description = format!(
+        "Processed copy of {} made with Spotify Sorter on {}",
+        source_name,
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    if !steps.is_empty() {
+        description.push_str(&format!(" ({})", steps.join(", ")));
+    }
+    description
+}
+
 #[tauri::command]
 pub fn open_url(url: String) -> Result<(), String> {
     open::that(&url).map_err(|e| e.to_string())
@@ -1175,16 +1364,12 @@ pub async fn create_backup(
 
     let client = {
         let spotify = state.spotify.lock().unwrap();
-        (spotify.client.clone(), spotify.playlists.clone())
+        spotify.client.clone()
     };
 
-    let (client, playlists) = client;
     let client = client.ok_or("Not authenticated")?;
 
-    let selected_playlists: Vec<&Playlist> = playlists
-        .iter()
-        .filter(|p| playlist_ids.contains(&p.id))
-        .collect();
+    let selected_playlists = resolve_playlists(&state, &client, &playlist_ids).await?;
 
     let total = selected_playlists.len();
 
@@ -1306,6 +1491,7 @@ pub async fn restore_from_file(
         spotify.client.clone()
     }
     .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
 
     // Extract Track IDs
     let mut track_ids = Vec::new();
@@ -1393,14 +1579,38 @@ pub fn clear_history() -> Result<(), String> {
 
 #[tauri::command]
 pub fn get_ignored_tracks() -> Result<Vec<IgnoredTrack>, String> {
-    let path = get_ignored_tracks_path();
-    if !path.exists() {
-        return Ok(Vec::new());
-    }
+    Ok(IGNORED_TRACKS_FILE.load(&get_ignored_tracks_path()))
+}
 
-    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let tracks: Vec<IgnoredTrack> = serde_json::from_str(&content).unwrap_or_default();
-    Ok(tracks)
+/// URIs of the tracks on the ignore list
+pub(crate) fn ignored_track_uris() -> std::collections::HashSet<String> {
+    get_ignored_tracks()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| t.id)
+        .collect()
+}
+
+#[tauri::command]
+pub fn get_ban_list() -> crate::bans::BanList {
+    crate::bans::load_ban_list()
+}
+
+/// Save the ban list. Track links and IDs are turned into URIs.
+#[tauri::command]
+pub fn save_ban_list(mut list: crate::bans::BanList) -> Result<(), String> {
+    for uri in list.track_uris.iter_mut() {
+        *uri = format!("spotify:track:{}", crate::spotify::parse_track_id(uri)?);
+    }
+    let trimmed = |items: &mut Vec<String>| {
+        items.retain(|s| !s.trim().is_empty());
+        for item in items.iter_mut() {
+            *item = item.trim().to_string();
+        }
+    };
+    trimmed(&mut list.artists);
+    trimmed(&mut list.albums);
+    crate::bans::save_ban_list(&list)
 }
 
 #[tauri::command]
@@ -1427,6 +1637,25 @@ pub async fn restore_snapshot(
         ));
     }
 
+    // 1c. Check if it's a playlist details edit
+    if let Some(backup) = &entry.details_backup {
+        if backup.is_empty() {
+            return Err(format!(
+                "Nothing to restore: {} was changed without a backup",
+                entry.playlist_name
+            ));
+        }
+        let client = {
+            let spotify = state.spotify.lock().unwrap();
+            spotify.client.clone()
+        }
+        .ok_or("Not authenticated")?;
+        require_scopes(&client).await?;
+
+        revert_playlist_details(&state, &client, &entry.playlist_id, backup).await?;
+        return Ok(format!("Restored details of {}", entry.playlist_name));
+    }
+
     println!("Restoring snapshot for playlist: {}", entry.playlist_name);
 
     // 2. Load backup
@@ -1454,6 +1683,7 @@ pub async fn restore_snapshot(
         spotify.client.clone()
     }
     .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
 
     // 4. Extract Track IDs from backup
     let mut track_ids = Vec::new();
@@ -1500,19 +1730,13 @@ pub async fn restore_snapshot(
 #[tauri::command]
 pub async fn remove_ignored_tracks(track_ids: Vec<String>) -> Result<(), String> {
     let path = get_ignored_tracks_path();
-    if !path.exists() {
-        return Ok(());
-    }
-
-    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let mut tracks: Vec<IgnoredTrack> = serde_json::from_str(&content).unwrap_or_default();
+    let mut tracks: Vec<IgnoredTrack> = IGNORED_TRACKS_FILE.load(&path);
 
     let initial_len = tracks.len();
     tracks.retain(|t| !track_ids.contains(&t.id));
 
     if tracks.len() != initial_len {
-        let json = serde_json::to_string_pretty(&tracks).map_err(|e| e.to_string())?;
-        fs::write(path, json).map_err(|e| e.to_string())?;
+        IGNORED_TRACKS_FILE.save(&path, &tracks)?;
     }
 
     Ok(())
@@ -1558,16 +1782,12 @@ pub async fn export_csv(
 
     let client = {
         let spotify = state.spotify.lock().unwrap();
-        (spotify.client.clone(), spotify.playlists.clone())
+        spotify.client.clone()
     };
 
-    let (client, playlists) = client;
     let client = client.ok_or("Not authenticated")?;
 
-    let selected_playlists: Vec<&Playlist> = playlists
-        .iter()
-        .filter(|p| playlist_ids.contains(&p.id))
-        .collect();
+    let selected_playlists = resolve_playlists(&state, &client, &playlist_ids).await?;
 
     for playlist in &selected_playlists {
         // Fetch tracks for export
@@ -1616,6 +1836,55 @@ pub async fn export_csv(
     ))
 }
 
+/// Resolve playlist inputs (IDs, URIs or URLs). Library playlists come from
+/// the session; others are fetched from Spotify.
+async fn resolve_playlists(
+    state: &State<'_, AppState>,
+    client: &SpotifyClient,
+    inputs: &[String],
+) -> Result<Vec<Playlist>, String> {
+    let (library, user_id) = {
+        let spotify = state.spotify.lock().unwrap();
+        (
+            spotify.playlists.clone(),
+            spotify.user_id.clone().unwrap_or_default(),
+        )
+    };
+
+    let mut resolved = Vec::new();
+    for input in inputs {
+        let id = parse_playlist_id(input)?;
+        match library.iter().find(|p| p.id == id) {
+            Some(playlist) => resolved.push(playlist.clone()),
+            None => {
+                resolved.push(crate::spotify::fetch_playlist_info(client, &id, &user_id).await?)
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Look up a playlist by ID, URI or URL and add it to the session's playlist
+/// list, so playlists outside the library can be selected like any other
+#[tauri::command]
+pub async fn open_playlist(state: State<'_, AppState>, input: String) -> Result<Playlist, String> {
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    }
+    .ok_or("Not authenticated")?;
+
+    let playlist = resolve_playlists(&state, &client, &[input])
+        .await?
+        .remove(0);
+
+    let mut spotify = state.spotify.lock().unwrap();
+    if !spotify.playlists.iter().any(|p| p.id == playlist.id) {
+        spotify.playlists.push(playlist.clone());
+    }
+    Ok(playlist)
+}
+
 fn sanitize_filename(name: &str) -> String {
     name.chars()
         .map(|c| {
@@ -1634,75 +1903,93 @@ fn escape_csv(s: &str) -> String {
     s.replace('"', "\"\"")
 }
 
-fn match_titles_relaxed(title1: &str, title2: &str) -> bool {
-    let t1_lower = title1.to_lowercase();
-    let t2_lower = title2.to_lowercase();
-
-    // Special handling for "remix", "vip", "bootleg", "edit" keywords
-    // These indicate fundamentally different versions
-    let strict_keywords = ["remix", "vip", "bootleg", "edit"];
-
-    for kw in strict_keywords {
-        let h1 = t1_lower.contains(kw);
-        let h2 = t2_lower.contains(kw);
-        if h1 != h2 {
-            // One is a remix/vip/etc and the other isn't -> different versions
-            return false;
-        }
-    }
-
-    // Clean both titles
-    let t1 = clean_title(title1);
-    let t2 = clean_title(title2);
-
-    // If they match exactly, we're good
-    if t1 == t2 {
-        return true;
-    }
+// ========================
+// Dynamic Playlists Commands
+// ========================
 
-    // Try stripping "original" and "original mix" from both and compare base titles
-    let t1_base = strip_original_suffix(&t1);
-    let t2_base = strip_original_suffix(&t2);
+use crate::dynamic::{
+    load_dynamic_configs, save_dynamic_configs, update_dynamic_playlist, DynamicPlaylistConfig,
+};
 
-    t1_base == t2_base
+/// Back up a dynamic playlist's target before a run changes it. Returns the
+/// backup's file name.
+pub(crate) fn backup_dynamic_target(
+    config: &DynamicPlaylistConfig,
+    tracks: &[crate::dynamic::TrackInfo],
+) -> Result<String, String> {
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let backup_filename = format!("{}_{}.json", sanitize_filename(&config.name), timestamp);
+    let backup_data = serde_json::json!({
+        "playlist_id": config.target_playlist_id,
+        "playlist_name": config.name,
+        "backup_time": timestamp,
+        "tracks": tracks
+    });
+    let json = serde_json::to_string_pretty(&backup_data).map_err(|e| e.to_string())?;
+    fs::write(get_backup_dir().join(&backup_filename), json)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    Ok(backup_filename)
 }
 
-/// Strips common "original" suffixes from a cleaned title
-fn strip_original_suffix(s: &str) -> String {
-    let s = s.trim();
-    // Order matters - check longer patterns first
-    let suffixes = [" original mix", " original"];
-    let mut result = s.to_string();
-    for suffix in suffixes {
-        if result.ends_with(suffix) {
-            result = result[..result.len() - suffix.len()].to_string();
-            break;
-        }
-    }
-    result.trim().to_string()
-}
+/// Log a dynamic run in the history. Undoing it restores the backup.
+pub(crate) fn log_dynamic_run(
+    app: &tauri::AppHandle,
+    config: &DynamicPlaylistConfig,
+    backup_file: String,
+    added: &[crate::dynamic::TrackInfo],
+    removed: &[crate::dynamic::TrackInfo],
+) -> Result<(), String> {
+    use tauri::Manager;
 
-fn clean_title(s: &str) -> String {
-    // Replace common separators with spaces
-    let s = s.replace(['-', '(', ')', '[', ']', '_'], " ");
+    let change = |change_type: &str, track: &crate::dynamic::TrackInfo| {
+        let is_add = change_type == "add";
+        ReviewChange {
+            id: uuid::Uuid::new_v4().to_string(),
+            change_type: change_type.to_string(),
+            new_title: is_add.then(|| track.name.clone()),
+            new_artist: is_add.then(|| track.artist.clone()),
+            new_album: is_add.then(|| track.album.clone()),
+            new_date: is_add.then(|| track.release_date.clone()),
+            rem_title: (!is_add).then(|| track.name.clone()),
+            rem_artist: (!is_add).then(|| track.artist.clone()),
+            rem_album: (!is_add).then(|| track.album.clone()),
+            rem_date: (!is_add).then(|| track.release_date.clone()),
+            track_uri: track.uri.clone(),
+            original_index: 0,
+            original_uri: track.uri.clone(),
+        }
+    };
+    let changes = added
+        .iter()
+        .map(|t| change("add", t))
+        .chain(removed.iter().map(|t| change("remove", t)))
+        .collect();
 
-    // Remove punctuation and extra whitespace, lowercase
-    s.chars()
-        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
-        .collect::<String>()
-        .to_lowercase()
-        .split_whitespace()
-        .collect::<Vec<_>>()
-        .join(" ")
+    let entry = HistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        playlist_name: config.name.clone(),
+        playlist_id: config.target_playlist_id.clone(),
+        action: format!(
+            "Dynamic update: {} added, {} removed",
+            added.len(),
+            removed.len()
+        ),
+        time: chrono::Local::now().format("%H:%M:%S").to_string(),
+        backup_file,
+        changes: Some(changes),
+        ignored: None,
+        dynamic_config_backup: None,
+        details_backup: None,
+    };
+    append_history(&app.state::<AppState>(), entry)
 }
 
-// ========================
-// Dynamic Playlists Commands
-// ========================
-
-use crate::dynamic::{
-    load_dynamic_configs, save_dynamic_configs, update_dynamic_playlist, DynamicPlaylistConfig,
-};
+/// Saved configs, schedules and ignored tracks that failed to load the
+/// last time they were read
+#[tauri::command]
+pub fn get_load_issues() -> Vec<crate::storage::LoadIssue> {
+    crate::storage::load_issues()
+}
 
 /// Get all saved dynamic playlist configurations
 #[tauri::command]
@@ -1710,10 +1997,27 @@ pub fn get_dynamic_configs() -> Result<Vec<DynamicPlaylistConfig>, String> {
     Ok(load_dynamic_configs())
 }
 
+/// Turn pasted links into IDs and check the filters compile
+fn normalize_dynamic_config(config: &mut DynamicPlaylistConfig) -> Result<(), String> {
+    config.target_playlist_id = parse_playlist_id(&config.target_playlist_id)?;
+    crate::dynamic::validate_filters(&config.filters)?;
+    for source in config.all_sources_mut() {
+        match source {
+            crate::dynamic::Source::Playlist { id } => *id = parse_playlist_id(id)?,
+            crate::dynamic::Source::Artist { id, .. } => *id = crate::spotify::parse_artist_id(id)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 /// Save a new or updated dynamic playlist configuration
 #[tauri::command]
-pub fn save_dynamic_config(config: DynamicPlaylistConfig) -> Result<(), String> {
+pub fn save_dynamic_config(mut config: DynamicPlaylistConfig) -> Result<(), String> {
+    normalize_dynamic_config(&mut config)?;
+
     let mut configs = load_dynamic_configs();
+    let config_id = config.id.clone();
 
     // Update existing or add new
     if let Some(pos) = configs.iter().position(|c| c.id == config.id) {
@@ -1722,6 +2026,13 @@ pub fn save_dynamic_config(config: DynamicPlaylistConfig) -> Result<(), String>
         configs.push(config);
     }
 
+    let (_, cyclic) = crate::dynamic::order_by_dependencies(&configs);
+    if cyclic.iter().any(|c| c.id == config_id) {
+        return Err(
+            "These sources would make dynamic playlists feed into each other in a loop".to_string(),
+        );
+    }
+
     save_dynamic_configs(&configs)
 }
 
@@ -1747,6 +2058,7 @@ pub fn delete_dynamic_config(id: String) -> Result<(), String> {
             changes: None,
             ignored: None,
             dynamic_config_backup: Some(serde_json::to_string(&config).unwrap_or_default()),
+            details_backup: None,
         };
 
         // Reuse get/save logic from apply_changes usually, but here we do it inline for simplicity
@@ -1789,12 +2101,6 @@ pub async fn run_dynamic_playlist_logic(
     state: &State<'_, AppState>,
     config_id: &String,
 ) -> Result<String, String> {
-    let configs = load_dynamic_configs();
-    let config = configs
-        .into_iter()
-        .find(|c| c.id == *config_id)
-        .ok_or("Config not found")?;
-
     // Clone the spotify client before releasing the lock
     let spotify = {
         let spotify_state = state.spotify.lock().map_err(|e| e.to_string())?;
@@ -1805,24 +2111,61 @@ pub async fn run_dynamic_playlist_logic(
             .clone()
     };
 
-    match update_dynamic_playlist(&spotify, &config, app).await {
-        Ok(count) => Ok(format!(
-            "Updated playlist '{}': {} tracks",
-            config.name, count
+    // Stay on this profile's files even if the user switches profile mid-run
+    crate::profiles::with_profile(
+        crate::profiles::current_profile_id(),
+        run_dynamic_config(app, &spotify, config_id),
+    )
+    .await
+}
+
+/// Run a dynamic playlist update of any profile with that profile's client
+pub async fn run_profile_dynamic_update(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    profile_id: &str,
+    config_id: &String,
+) -> Result<String, String> {
+    let spotify = client_for_profile(app, state, profile_id).await?;
+    crate::profiles::with_profile(
+        profile_id.to_string(),
+        run_dynamic_config(app, &spotify, config_id),
+    )
+    .await
+}
+
+async fn run_dynamic_config(
+    app: &tauri::AppHandle,
+    spotify: &SpotifyClient,
+    config_id: &String,
+) -> Result<String, String> {
+    let configs = load_dynamic_configs();
+    let config = configs
+        .into_iter()
+        .find(|c| c.id == *config_id)
+        .ok_or("Config not found")?;
+
+    require_scopes(spotify).await?;
+
+    match update_dynamic_playlist(spotify, &config, app).await {
+        Ok(result) => Ok(format!(
+            "Updated playlist '{}': {}",
+            config.name,
+            result.summary()
         )),
         Err(e) => Err(format!("Failed to update '{}': {}", config.name, e)),
     }
 }
 
-/// Run all dynamic playlist updates (for automation)
+/// Show what updating a config would do, without changing the playlist. The
+/// config doesn't need to be saved, so edits can be checked first.
 #[tauri::command]
-pub async fn run_all_dynamic_updates(
+pub async fn preview_dynamic_update(
     app: tauri::AppHandle,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
-    let configs = load_dynamic_configs();
-
-    // Clone the spotify client before releasing the lock
+    mut config: DynamicPlaylistConfig,
+) -> Result<crate::dynamic::DynamicPreview, String> {
+    normalize_dynamic_config(&mut config)?;
     let spotify = {
         let spotify_state = state.spotify.lock().map_err(|e| e.to_string())?;
         spotify_state
@@ -1832,57 +2175,901 @@ pub async fn run_all_dynamic_updates(
             .clone()
     };
 
-    let mut results = Vec::new();
+    require_scopes(&spotify).await?;
+    crate::dynamic::preview_dynamic_playlist(&spotify, &config, &app).await
+}
 
-    for config in &configs {
-        match update_dynamic_playlist(&spotify, config, &app).await {
-            Ok(count) => results.push(format!("{}: {} tracks", config.name, count)),
-            Err(e) => results.push(format!("{}: Error - {}", config.name, e)),
+/// Export configs, the configs they read from and their schedules to a
+/// bundle in the exports folder
+#[tauri::command]
+pub fn export_config_bundle(
+    state: State<'_, AppState>,
+    config_ids: Vec<String>,
+) -> Result<String, String> {
+    let all_configs = load_dynamic_configs();
+    let mut ids: Vec<String> = config_ids;
+    let mut i = 0;
+    while i < ids.len() {
+        if let Some(config) = all_configs.iter().find(|c| c.id == ids[i]) {
+            for source in config.all_sources() {
+                if let crate::dynamic::Source::DynamicConfig { id } = source {
+                    if !ids.contains(id) {
+                        ids.push(id.clone());
+                    }
+                }
+            }
         }
+        i += 1;
+    }
+    let configs: Vec<DynamicPlaylistConfig> = all_configs
+        .into_iter()
+        .filter(|c| ids.contains(&c.id))
+        .collect();
+    if configs.is_empty() {
+        return Err("No configs to export".to_string());
     }
 
-    Ok(results)
-}
+    let playlists = state.spotify.lock().unwrap().playlists.clone();
+    let bundle =
+        crate::bundles::build_bundle(&configs, &crate::scheduler::load_schedules(), &playlists);
 
-// ============ COMPARE PLAYLISTS ============
+    let exports_dir = get_exports_dir();
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let filepath = exports_dir.join(format!("dynamic_playlists_{}.json", timestamp));
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| e.to_string())?;
+    fs::write(&filepath, json).map_err(|e| format!("Failed to write bundle: {}", e))?;
 
-/// Duplicate track found across playlists
-#[derive(Serialize, Clone)]
-pub struct DuplicateTrack {
-    pub track_id: String,
-    pub track_uri: String,
-    pub name: String,
-    pub artist: String,
-    pub found_in_playlists: Vec<String>, // playlist IDs where this track appears
-}
+    open::that(&exports_dir).ok();
 
-/// Result of comparing playlists
-#[derive(Serialize)]
-pub struct CompareResult {
-    pub duplicates: Vec<DuplicateTrack>,
-    pub playlists_compared: usize,
+    let mut summary = format!(
+        "Exported {} config(s) and {} schedule(s) to {:?}",
+        bundle.configs.len(),
+        bundle.schedules.len(),
+        filepath
+    );
+    for config in &configs {
+        if let Some(reason) = crate::bundles::server_unsupported(config) {
+            summary.push_str(&format!(
+                "\n'{}' {}, which the server can't run: it's disabled there, with its schedule off",
+                config.name, reason
+            ));
+        }
+    }
+    Ok(summary)
 }
 
-/// Compare selected playlists to find duplicate tracks
+/// Import a bundle exported by this app or the server. Playlists are matched
+/// by ID, then by name; targets without a match are created when
+/// `create_targets` is set.
 #[tauri::command]
-pub async fn compare_playlists(
+pub async fn import_config_bundle(
+    app: tauri::AppHandle,
     state: State<'_, AppState>,
-    playlist_ids: Vec<String>,
-) -> Result<CompareResult, String> {
-    if playlist_ids.len() < 2 {
-        return Err("Please select at least 2 playlists to compare".to_string());
-    }
-
-    // No limit - rate limiting handles API constraints
+    path: String,
+    create_targets: bool,
+) -> Result<String, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let bundle = crate::bundles::parse_bundle(&content)?;
 
-    let (client, playlists) = {
+    let (client, user_id) = {
         let spotify = state.spotify.lock().unwrap();
-        (spotify.client.clone(), spotify.playlists.clone())
+        (
+            spotify.client.clone().ok_or("Not authenticated")?,
+            spotify.user_id.clone().ok_or("Not authenticated")?,
+        )
     };
 
-    let client = client.ok_or("Not authenticated")?;
+    let mut configs = load_dynamic_configs();
+    let mut schedules = crate::scheduler::load_schedules();
+    let mut notes = Vec::new();
+
+    // Configs imported before, or exported from here, are replaced; others get a new ID
+    let mut id_map: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    for entry in &bundle.configs {
+        if let Some(id) = &entry.id {
+            let new_id = configs
+                .iter()
+                .find(|c| &c.id == id || c.imported_from.as_ref() == Some(id))
+                .map(|c| c.id.clone())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            id_map.insert(id.clone(), new_id);
+        }
+    }
 
-    // Map of track URI -> (track info, list of playlist IDs)
+    let mut imported = 0;
+    let mut created = 0;
+    for entry in &bundle.configs {
+        let mut config = match entry.to_config() {
+            Ok(config) => config,
+            Err(e) => {
+                notes.push(format!("Skipped '{}': {}", entry.name, e));
+                continue;
+            }
+        };
+        config.id = entry
+            .id
+            .as_ref()
+            .and_then(|id| id_map.get(id))
+            .cloned()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        config.imported_from = entry.id.clone().filter(|id| *id != config.id);
+
+        let playlists = state.spotify.lock().unwrap().playlists.clone();
+        let config_name = config.name.clone();
+        for source in config.all_sources_mut() {
+            match source {
+                crate::dynamic::Source::Playlist { id } => {
+                    let name = bundle.playlist_names.get(id.as_str()).map(String::as_str);
+                    // Playlists this account doesn't follow may still be public
+                    if let Some(found) = crate::bundles::find_playlist(&playlists, id, name, false)
+                    {
+                        *id = found;
+                    }
+                }
+                crate::dynamic::Source::DynamicConfig { id } => {
+                    if let Some(new_id) = id_map.get(id.as_str()) {
+                        *id = new_id.clone();
+                    } else if !configs.iter().any(|c| &c.id == id) {
+                        notes.push(format!(
+                            "'{}': reads from a dynamic playlist that wasn't exported",
+                            config_name
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Err(e) = normalize_dynamic_config(&mut config) {
+            notes.push(format!("Skipped '{}': {}", config.name, e));
+            continue;
+        }
+
+        // Only create a target once the config is known to be valid
+        let target_name = Some(entry.target_playlist_name.as_str())
+            .filter(|n| !n.is_empty())
+            .or(bundle
+                .playlist_names
+                .get(&entry.target_playlist_id)
+                .map(String::as_str));
+        match crate::bundles::find_playlist(
+            &playlists,
+            &config.target_playlist_id,
+            target_name,
+            true,
+        ) {
+            Some(id) => config.target_playlist_id = id,
+            None if create_targets => {
+                let name = target_name.unwrap_or(&config.name).to_string();
+                let playlist = match crate::spotify::create_playlist(
+                    &client,
+                    &user_id,
+                    &name,
+                    &format!("Dynamic playlist: {}", config.name),
+                )
+                .await
+                {
+                    Ok(playlist) => playlist,
+                    Err(e) => {
+                        notes.push(format!("Skipped '{}': {}", config.name, e));
+                        continue;
+                    }
+                };
+                state
+                    .spotify
+                    .lock()
+                    .unwrap()
+                    .playlists
+                    .insert(0, playlist.clone());
+                config.target_playlist_id = playlist.id;
+                created += 1;
+            }
+            // The ID belongs to another account, so never run against it
+            None => {
+                notes.push(format!(
+                    "Skipped '{}': target playlist {} not found",
+                    config.name,
+                    target_name.unwrap_or(&config.target_playlist_id)
+                ));
+                continue;
+            }
+        }
+
+        if let Some(pos) = configs.iter().position(|c| c.id == config.id) {
+            configs[pos] = config;
+        } else {
+            configs.push(config);
+        }
+        imported += 1;
+    }
+    save_dynamic_configs(&configs)?;
+    if created > 0 {
+        publish_playlists(&app, &state);
+    }
+
+    let mut schedule_count = 0;
+    for schedule in &bundle.schedules {
+        let Some(config_id) = id_map.get(&schedule.config_id) else {
+            continue;
+        };
+        if !configs.iter().any(|c| &c.id == config_id) {
+            continue;
+        }
+        schedules.retain(|s| s.id != schedule.id);
+        schedules.push(crate::scheduler::DesktopSchedule {
+            config_id: config_id.clone(),
+            last_run: None,
+            enabled: schedule.enabled || bundle.paused_for_server.contains(&schedule.id),
+            ..schedule.clone()
+        });
+        schedule_count += 1;
+    }
+    crate::scheduler::save_schedules(&schedules);
+
+    let mut summary = format!(
+        "Imported {} config(s) and {} schedule(s)",
+        imported, schedule_count
+    );
+    if created > 0 {
+        summary.push_str(&format!(", created {} target playlist(s)", created));
+    }
+    for note in notes {
+        summary.push_str(&format!("\n{}", note));
+    }
+    Ok(summary)
+}
+
+/// Run all dynamic playlist updates (for automation)
+#[tauri::command]
+pub async fn run_all_dynamic_updates(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let configs = load_dynamic_configs();
+
+    // Clone the spotify client before releasing the lock
+    let spotify = {
+        let spotify_state = state.spotify.lock().map_err(|e| e.to_string())?;
+        spotify_state
+            .client
+            .as_ref()
+            .ok_or("Not authenticated")?
+            .clone()
+    };
+
+    let mut results = Vec::new();
+
+    // Configs feeding other configs go first, and their results are reused
+    let (ordered, cyclic) = crate::dynamic::order_by_dependencies(&configs);
+    let mut computed = crate::dynamic::ComputedResults::new();
+    let profile_id = crate::profiles::current_profile_id();
+    for config in ordered {
+        let update =
+            crate::dynamic::update_dynamic_playlist_in_run(&spotify, config, &app, &mut computed);
+        // Stay on this profile's files even if the user switches profile mid-run
+        match crate::profiles::with_profile(profile_id.clone(), update).await {
+            Ok(result) => results.push(format!("{}: {}", config.name, result.summary())),
+            Err(e) => results.push(format!("{}: Error - {}", config.name, e)),
+        }
+    }
+    for config in cyclic {
+        results.push(format!(
+            "{}: Error - Skipped, its dynamic playlist sources form a loop",
+            config.name
+        ));
+    }
+
+    Ok(results)
+}
+
+// ========================
+// Playlist List Commands
+// ========================
+
+/// Tell the frontend the session's playlist list changed
+fn publish_playlists(app: &tauri::AppHandle, state: &State<'_, AppState>) {
+    use tauri::Emitter;
+    let playlists = state.spotify.lock().unwrap().playlists.clone();
+    let _ = app.emit("playlists-changed", playlists);
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshPlaylistsResult {
+    pub playlists: Vec<Playlist>,
+    pub changes: crate::spotify::PlaylistListChanges,
+}
+
+/// Refetch the playlist list, bypassing the cache
+#[tauri::command]
+pub async fn refresh_playlists(
+    state: State<'_, AppState>,
+) -> Result<RefreshPlaylistsResult, String> {
+    let (client, user_id) = {
+        let spotify = state.spotify.lock().unwrap();
+        (spotify.client.clone(), spotify.user_id.clone())
+    };
+    let client = client.ok_or("Not authenticated")?;
+    let user_id = user_id.ok_or("Not authenticated")?;
+
+    let (playlists, changes) = crate::spotify::refresh_all_playlists(&client, &user_id).await?;
+    println!(
+        "Refreshed playlists: {} added, {} removed, {} changed",
+        changes.added.len(),
+        changes.removed.len(),
+        changes.changed.len()
+    );
+
+    state.spotify.lock().unwrap().playlists = playlists.clone();
+    Ok(RefreshPlaylistsResult { playlists, changes })
+}
+
+/// Back up a playlist's tracks, then remove it from the library
+#[tauri::command]
+pub async fn delete_playlist(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    playlist_id: String,
+) -> Result<String, String> {
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    };
+    let client = client.ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
+
+    let playlist_id = parse_playlist_id(&playlist_id)?;
+    let (name, tracks) = fetch_playlist_tracks(&client, &playlist_id, &app).await?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let backup_path =
+        get_backup_dir().join(format!("{}_{}.json", sanitize_filename(&name), timestamp));
+    let backup_data = serde_json::json!({
+        "playlist_id": playlist_id,
+        "playlist_name": name,
+        "backup_time": timestamp,
+        "track_count": tracks.len(),
+        "tracks": tracks
+    });
+    fs::write(
+        &backup_path,
+        serde_json::to_string_pretty(&backup_data).unwrap(),
+    )
+    .map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    crate::spotify::delete_playlist(&client, &playlist_id).await?;
+
+    state
+        .spotify
+        .lock()
+        .unwrap()
+        .playlists
+        .retain(|p| p.id != playlist_id);
+    publish_playlists(&app, &state);
+
+    Ok(format!("Deleted {} (backup saved)", name))
+}
+
+// ========================
+// Playlist Details Commands
+// ========================
+
+/// Spotify rejects cover images above 256 KB (base64 encoded)
+const MAX_COVER_BASE64_LEN: usize = 256 * 1024;
+
+/// Append an entry to the history file
+fn append_history(state: &State<'_, AppState>, entry: HistoryEntry) -> Result<(), String> {
+    let _lock = state.history_lock.lock().unwrap();
+
+    let history_path = get_history_path();
+    let mut hist: Vec<HistoryEntry> = if history_path.exists() {
+        let content = fs::read_to_string(&history_path)
+            .map_err(|e| format!("Failed to read history file: {}", e))?;
+        serde_json::from_str(&content).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    hist.push(entry);
+    let json = serde_json::to_string_pretty(&hist).map_err(|e| e.to_string())?;
+    fs::write(&history_path, json).map_err(|e| format!("Failed to write history: {}", e))
+}
+
+/// Update a playlist in the session and the cached playlist list
+fn update_known_playlist(
+    state: &State<'_, AppState>,
+    playlist_id: &str,
+    f: impl Fn(&mut Playlist),
+) {
+    {
+        let mut spotify = state.spotify.lock().unwrap();
+        if let Some(playlist) = spotify.playlists.iter_mut().find(|p| p.id == playlist_id) {
+            f(playlist);
+        }
+    }
+    crate::spotify::update_playlists_cache(|playlists| {
+        if let Some(playlist) = playlists.iter_mut().find(|p| p.id == playlist_id) {
+            f(playlist);
+        }
+    });
+}
+
+/// Change the name, description, visibility or collaborative flag of a playlist.
+/// Fields left out stay as they are.
+#[tauri::command]
+pub async fn update_playlist_details(
+    state: State<'_, AppState>,
+    playlist_id: String,
+    name: Option<String>,
+    description: Option<String>,
+    public: Option<bool>,
+    collaborative: Option<bool>,
+) -> Result<(), String> {
+    if name.as_deref().is_some_and(|n| n.trim().is_empty()) {
+        return Err("Playlist name cannot be empty".to_string());
+    }
+    let playlist_id = parse_playlist_id(&playlist_id)?;
+
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    }
+    .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
+
+    let pid =
+        PlaylistId::from_id(&playlist_id).map_err(|e| format!("Invalid playlist ID: {}", e))?;
+    let current = client
+        .playlist(pid.clone(), None, None)
+        .await
+        .map_err(|e| format!("Failed to get playlist: {}", e))?;
+
+    // Spotify only allows collaborative playlists that are private
+    let will_be_public = public.unwrap_or(current.public.unwrap_or(false));
+    let will_be_collaborative = collaborative.unwrap_or(current.collaborative);
+    if will_be_public && will_be_collaborative {
+        return Err("Collaborative playlists must be private".to_string());
+    }
+
+    // Remember what we're about to overwrite
+    let backup = PlaylistDetailsBackup {
+        name: name.as_ref().map(|_| current.name.clone()),
+        description: description
+            .as_ref()
+            .map(|_| current.description.clone().unwrap_or_default()),
+        public: public.map(|_| current.public.unwrap_or(false)),
+        collaborative: collaborative.map(|_| current.collaborative),
+        cover_file: None,
+    };
+
+    client
+        .playlist_change_detail(
+            pid,
+            name.as_deref(),
+            public,
+            description.as_deref(),
+            collaborative,
+        )
+        .await
+        .map_err(|e| format!("Failed to update playlist: {}", e))?;
+
+    let action = match &name {
+        Some(new_name) if *new_name != current.name => {
+            format!("Renamed '{}' to '{}'", current.name, new_name)
+        }
+        _ => "Edited playlist details".to_string(),
+    };
+    append_history(
+        &state,
+        HistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            playlist_name: name.clone().unwrap_or(current.name),
+            playlist_id: playlist_id.clone(),
+            action,
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            backup_file: "".to_string(),
+            changes: None,
+            ignored: None,
+            dynamic_config_backup: None,
+            details_backup: Some(backup),
+        },
+    )?;
+
+    update_known_playlist(&state, &playlist_id, |p| {
+        if let Some(name) = &name {
+            p.name = name.clone();
+        }
+        if let Some(public) = public {
+            p.is_public = public;
+        }
+        if let Some(collaborative) = collaborative {
+            p.collaborative = collaborative;
+        }
+    });
+
+    Ok(())
+}
+
+/// Replace the cover of a playlist with a JPEG file
+#[tauri::command]
+pub async fn upload_playlist_cover(
+    state: State<'_, AppState>,
+    playlist_id: String,
+    image_path: String,
+) -> Result<String, String> {
+    use base64::Engine;
+
+    let playlist_id = parse_playlist_id(&playlist_id)?;
+    let image = fs::read(&image_path).map_err(|e| format!("Failed to read image: {}", e))?;
+    if !image.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Err("Cover image must be a JPEG".to_string());
+    }
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&image);
+    if encoded.len() > MAX_COVER_BASE64_LEN {
+        return Err("Cover image is too large (max. 190 KB)".to_string());
+    }
+
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    }
+    .ok_or("Not authenticated")?;
+    require_scopes_of(&client, &SpotifyState::cover_scopes()).await?;
+
+    let pid =
+        PlaylistId::from_id(&playlist_id).map_err(|e| format!("Invalid playlist ID: {}", e))?;
+    let current = client
+        .playlist(pid.clone(), None, None)
+        .await
+        .map_err(|e| format!("Failed to get playlist: {}", e))?;
+
+    // Keep the old cover so the change can be undone. Images are sorted
+    // largest first; playlists without one use a generated mosaic.
+    let (cover_file, not_saved) = match current.images.first() {
+        Some(old_cover) => match download_cover(&old_cover.url).await {
+            Ok(bytes) => {
+                let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+                let filename = format!(
+                    "{}_{}_cover.jpg",
+                    sanitize_filename(&current.name),
+                    timestamp
+                );
+                fs::write(get_backup_dir().join(&filename), bytes)
+                    .map_err(|e| format!("Failed to back up cover: {}", e))?;
+                (Some(filename), None)
+            }
+            Err(e) => {
+                println!("Could not back up current cover: {}", e);
+                (
+                    None,
+                    Some(format!("the old cover couldn't be downloaded ({})", e)),
+                )
+            }
+        },
+        None => (
+            None,
+            Some("the playlist had no cover of its own".to_string()),
+        ),
+    };
+
+    client
+        .playlist_upload_cover_image(pid, &encoded)
+        .await
+        .map_err(|e| format!("Failed to upload cover: {}", e))?;
+
+    let mut action = "Changed cover image".to_string();
+    if let Some(reason) = &not_saved {
+        action.push_str(&format!(" (can't be undone: {})", reason));
+    }
+    append_history(
+        &state,
+        HistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            playlist_name: current.name,
+            playlist_id,
+            action,
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            backup_file: "".to_string(),
+            changes: None,
+            ignored: None,
+            dynamic_config_backup: None,
+            details_backup: Some(PlaylistDetailsBackup {
+                cover_file,
+                ..Default::default()
+            }),
+        },
+    )?;
+
+    Ok(match not_saved {
+        Some(reason) => format!("Cover updated, but it can't be undone: {}", reason),
+        None => "Cover updated".to_string(),
+    })
+}
+
+async fn download_cover(url: &str) -> Result<Vec<u8>, String> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+/// Put back the details saved in a history entry
+async fn revert_playlist_details(
+    state: &State<'_, AppState>,
+    client: &SpotifyClient,
+    playlist_id: &str,
+    backup: &PlaylistDetailsBackup,
+) -> Result<(), String> {
+    use base64::Engine;
+
+    let pid =
+        PlaylistId::from_id(playlist_id).map_err(|e| format!("Invalid playlist ID: {}", e))?;
+
+    if backup.name.is_some()
+        || backup.description.is_some()
+        || backup.public.is_some()
+        || backup.collaborative.is_some()
+    {
+        client
+            .playlist_change_detail(
+                pid.clone(),
+                backup.name.as_deref(),
+                backup.public,
+                backup.description.as_deref(),
+                backup.collaborative,
+            )
+            .await
+            .map_err(|e| format!("Failed to restore playlist details: {}", e))?;
+    }
+
+    if let Some(cover_file) = &backup.cover_file {
+        let image = fs::read(get_backup_dir().join(cover_file))
+            .map_err(|e| format!("Cover backup not found: {}", e))?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&image);
+        client
+            .playlist_upload_cover_image(pid, &encoded)
+            .await
+            .map_err(|e| format!("Failed to restore cover: {}", e))?;
+    }
+
+    update_known_playlist(state, playlist_id, |p| {
+        if let Some(name) = &backup.name {
+            p.name = name.clone();
+        }
+        if let Some(public) = backup.public {
+            p.is_public = public;
+        }
+        if let Some(collaborative) = backup.collaborative {
+            p.collaborative = collaborative;
+        }
+    });
+
+    Ok(())
+}
+
+// ========================
+// Fork Commands
+// ========================
+
+use crate::forks::{ForkLink, ForkSyncSummary, LocalPlaceholder};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkResult {
+    playlist: Playlist,
+    /// Local files that couldn't be copied
+    local_placeholders: Vec<LocalPlaceholder>,
+}
+
+/// Copy a followed or public playlist into a new editable playlist
+#[tauri::command]
+pub async fn fork_playlist(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    playlist_id: String,
+    name: Option<String>,
+) -> Result<ForkResult, String> {
+    let (client, user_id) = {
+        let spotify = state.spotify.lock().unwrap();
+        (spotify.client.clone(), spotify.user_id.clone())
+    };
+    let client = client.ok_or("Not authenticated")?;
+    let user_id = user_id.ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
+
+    let playlist_id = parse_playlist_id(&playlist_id)?;
+    let (playlist, link) =
+        crate::forks::fork_playlist(&client, &user_id, &playlist_id, name, &app).await?;
+    state
+        .spotify
+        .lock()
+        .unwrap()
+        .playlists
+        .insert(0, playlist.clone());
+    publish_playlists(&app, &state);
+
+    Ok(ForkResult {
+        playlist,
+        local_placeholders: link.local_placeholders,
+    })
+}
+
+#[tauri::command]
+pub fn get_forks() -> Vec<ForkLink> {
+    crate::forks::load_forks()
+}
+
+/// Pull changes of the original playlist into a fork
+#[tauri::command]
+pub async fn sync_fork(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    fork_id: String,
+) -> Result<ForkSyncSummary, String> {
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    }
+    .ok_or("Not authenticated")?;
+    require_scopes(&client).await?;
+
+    let link = crate::forks::find_fork(&fork_id).ok_or("Playlist is not a fork")?;
+    crate::forks::sync_fork(&client, &link, &app).await
+}
+
+/// Forget the original of a fork. The fork itself stays.
+#[tauri::command]
+pub fn unlink_fork(fork_id: String) -> Result<(), String> {
+    let mut forks = crate::forks::load_forks();
+    forks.retain(|f| f.fork_id != fork_id);
+    crate::forks::save_forks(&forks)
+}
+
+// ========================
+// Profile Commands
+// ========================
+
+use crate::profiles::{Profile, ProfilesFile};
+
+/// Get a client for any profile: the active session, a session kept from
+/// before a profile switch, or one restored from the profile's saved credentials
+pub async fn client_for_profile(
+    app: &tauri::AppHandle,
+    state: &State<'_, AppState>,
+    profile_id: &str,
+) -> Result<SpotifyClient, String> {
+    if crate::profiles::load_profiles().active == profile_id {
+        let spotify = state.spotify.lock().unwrap();
+        return spotify
+            .client
+            .clone()
+            .ok_or("Not authenticated".to_string());
+    }
+
+    let key = {
+        let sessions = state.sessions.lock().unwrap();
+        match sessions.get(profile_id) {
+            Some(session) => match &session.client {
+                Some(client) => return Ok(client.clone()),
+                None => session.credentials_key.clone(),
+            },
+            None => None,
+        }
+    };
+
+    let (creds, key, client) = crate::profiles::with_profile(profile_id.to_string(), async {
+        let (mut creds, key) = match load_saved_credentials(key.as_ref())? {
+            Opened::Unlocked(creds, key) => (creds, key),
+            Opened::Locked => return Err("Profile is locked by a passphrase".to_string()),
+            _ => return Err("Profile is not logged in".to_string()),
+        };
+        let client = restore_client(app, profile_id, &mut creds, &key).await?;
+        Ok((creds, key, client))
+    })
+    .await?;
+
+    let mut sessions = state.sessions.lock().unwrap();
+    let session = sessions.entry(profile_id.to_string()).or_default();
+    session.client_id = Some(creds.client_id);
+    session.client_secret = creds.client_secret;
+    session.access_token = creds.token.map(|t| t.access_token);
+    session.refresh_token = creds.refresh_token;
+    session.client = Some(client.clone());
+    session.credentials_key = Some(key);
+
+    Ok(client)
+}
+
+#[tauri::command]
+pub fn get_profiles() -> ProfilesFile {
+    crate::profiles::load_profiles()
+}
+
+#[tauri::command]
+pub fn add_profile(name: String) -> Result<Profile, String> {
+    crate::profiles::add_profile(&name)
+}
+
+/// Switch the active profile. The previous session stays in memory so its
+/// schedules keep running; the frontend calls `check_auth` afterwards.
+#[tauri::command]
+pub fn switch_profile(
+    app: tauri::AppHandle,
+    state: State<AppState>,
+    profile_id: String,
+) -> Result<(), String> {
+    let current = crate::profiles::load_profiles().active;
+    if current == profile_id {
+        return Ok(());
+    }
+
+    crate::profiles::set_active_profile(&profile_id)?;
+
+    {
+        let mut sessions = state.sessions.lock().unwrap();
+        let mut spotify = state.spotify.lock().unwrap();
+        let previous = std::mem::take(&mut *spotify);
+        sessions.insert(current, previous);
+        *spotify = sessions.remove(&profile_id).unwrap_or_default();
+    }
+
+    // Tray lists the dynamic configs of the active profile
+    crate::tray::refresh_tray(&app).ok();
+    Ok(())
+}
+
+/// Remove a profile and delete its credentials, caches, history and configs
+#[tauri::command]
+pub fn remove_profile(state: State<AppState>, profile_id: String) -> Result<(), String> {
+    crate::profiles::remove_profile(&profile_id)?;
+    state.sessions.lock().unwrap().remove(&profile_id);
+    Ok(())
+}
+
+// ============ COMPARE PLAYLISTS ============
+
+/// Duplicate track found across playlists
+#[derive(Serialize, Clone)]
+pub struct DuplicateTrack {
+    pub track_id: String,
+    pub track_uri: String,
+    pub name: String,
+    pub artist: String,
+    pub found_in_playlists: Vec<String>, // playlist IDs where this track appears
+}
+
+/// Result of comparing playlists
+#[derive(Serialize)]
+pub struct CompareResult {
+    pub duplicates: Vec<DuplicateTrack>,
+    pub playlists_compared: usize,
+}
+
+/// Compare selected playlists to find duplicate tracks
+#[tauri::command]
+pub async fn compare_playlists(
+    state: State<'_, AppState>,
+    playlist_ids: Vec<String>,
+) -> Result<CompareResult, String> {
+    if playlist_ids.len() < 2 {
+        return Err("Please select at least 2 playlists to compare".to_string());
+    }
+
+    // No limit - rate limiting handles API constraints
+
+    let client = {
+        let spotify = state.spotify.lock().unwrap();
+        spotify.client.clone()
+    };
+
+    let client = client.ok_or("Not authenticated")?;
+
+    let playlists = resolve_playlists(&state, &client, &playlist_ids).await?;
+    let playlist_ids: Vec<String> = playlists.iter().map(|p| p.id.clone()).collect();
+
+    // Map of track URI -> (track info, list of playlist IDs)
     let mut track_map: std::collections::HashMap<String, (String, String, String, Vec<String>)> =
         std::collections::HashMap::new();
 
@@ -1978,6 +3165,7 @@ pub async fn remove_track_from_playlist(
 
     let client = client.ok_or("Not authenticated")?;
 
+    let playlist_id = parse_playlist_id(&playlist_id)?;
     let pid =
         PlaylistId::from_id(&playlist_id).map_err(|e| format!("Invalid playlist ID: {}", e))?;
 
@@ -2133,13 +3321,16 @@ pub async fn export_m3u(
     let local_tracks = scan_music_folder(&music_folder);
     println!("Found {} local audio files", local_tracks.len());
 
-    let (client, playlists) = {
+    let client = {
         let spotify = state.spotify.lock().unwrap();
-        (spotify.client.clone(), spotify.playlists.clone())
+        spotify.client.clone()
     };
 
     let client = client.ok_or("Not authenticated")?;
 
+    let playlists = resolve_playlists(&state, &client, &playlist_ids).await?;
+    let playlist_ids: Vec<String> = playlists.iter().map(|p| p.id.clone()).collect();
+
     let exports_dir = match &output_folder {
         Some(folder) if !folder.is_empty() => PathBuf::from(folder),
         _ => get_exports_dir(),
@@ -1,7 +1,8 @@
+use crate::spotify::SpotifyClient;
+use crate::storage::{DataFile, ListFile};
 use rspotify::prelude::*;
-use rspotify::AuthCodeSpotify;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -11,8 +12,135 @@ use tauri::Emitter; // Import Emitter
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum Source {
-    Playlist { id: String },
+    Playlist {
+        id: String,
+    },
     LikedSongs,
+    /// An artist's discography. `include_groups` takes Spotify's album groups:
+    /// album, single, appears_on and compilation.
+    #[serde(rename_all = "camelCase")]
+    Artist {
+        id: String,
+        #[serde(default = "default_include_groups")]
+        include_groups: Vec<String>,
+        /// Only releases not seen by a previous run of the same config
+        #[serde(default)]
+        new_releases_only: bool,
+    },
+    /// Every track of every saved album
+    SavedAlbums,
+    TopTracks {
+        #[serde(default)]
+        term: TopTracksTerm,
+    },
+    /// The last 50 played tracks, which is all Spotify keeps
+    RecentlyPlayed,
+    /// The computed result of another dynamic config, whether or not its
+    /// target playlist was updated
+    DynamicConfig {
+        id: String,
+    },
+}
+
+/// Time frame of the user's top tracks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TopTracksTerm {
+    ShortTerm, // About the last 4 weeks
+    #[default]
+    MediumTerm, // About the last 6 months
+    LongTerm,  // Several years
+}
+
+fn default_include_groups() -> Vec<String> {
+    vec!["album".to_string(), "single".to_string()]
+}
+
+/// On the first new-releases run there's nothing seen yet; releases this
+/// recent count as new so the playlist doesn't start out empty
+const NEW_RELEASE_SEED_DAYS: i64 = 28;
+
+/// Sources combined with set operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum SourceExpr {
+    Source {
+        source: Source,
+    },
+    /// Tracks in any item
+    Union {
+        items: Vec<SourceExpr>,
+        #[serde(default)]
+        identity: TrackIdentity,
+    },
+    /// Tracks in every item
+    Intersection {
+        items: Vec<SourceExpr>,
+        #[serde(default)]
+        identity: TrackIdentity,
+    },
+    /// Tracks in `from` but in none of `subtract`
+    Difference {
+        from: Box<SourceExpr>,
+        subtract: Vec<SourceExpr>,
+        #[serde(default)]
+        identity: TrackIdentity,
+    },
+}
+
+/// When two tracks count as the same in a set operation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackIdentity {
+    #[default]
+    Uri,
+    /// Same normalized title and first artist, so other releases of a song match too
+    DuplicateKey,
+}
+
+impl TrackIdentity {
+    fn key(&self, track: &TrackInfo) -> String {
+        match self {
+            TrackIdentity::Uri => track.uri.clone(),
+            TrackIdentity::DuplicateKey => track.to_app_track().duplicate_key(),
+        }
+    }
+
+    fn keys(&self, tracks: &[TrackInfo]) -> HashSet<String> {
+        tracks.iter().map(|t| self.key(t)).collect()
+    }
+}
+
+impl SourceExpr {
+    /// Every source in the expression
+    pub fn sources(&self) -> Vec<&Source> {
+        match self {
+            SourceExpr::Source { source } => vec![source],
+            SourceExpr::Union { items, .. } | SourceExpr::Intersection { items, .. } => {
+                items.iter().flat_map(|item| item.sources()).collect()
+            }
+            SourceExpr::Difference { from, subtract, .. } => from
+                .sources()
+                .into_iter()
+                .chain(subtract.iter().flat_map(|item| item.sources()))
+                .collect(),
+        }
+    }
+
+    pub fn sources_mut(&mut self) -> Vec<&mut Source> {
+        match self {
+            SourceExpr::Source { source } => vec![source],
+            SourceExpr::Union { items, .. } | SourceExpr::Intersection { items, .. } => items
+                .iter_mut()
+                .flat_map(|item| item.sources_mut())
+                .collect(),
+            SourceExpr::Difference { from, subtract, .. } => from
+                .sources_mut()
+                .into_iter()
+                .chain(subtract.iter_mut().flat_map(|item| item.sources_mut()))
+                .collect(),
+        }
+    }
 }
 
 /// How to update the target playlist
@@ -22,6 +150,20 @@ pub enum UpdateMode {
     Replace, // Clear and replace all tracks
     Merge,   // Add new tracks and re-sort
     Append,  // Add new tracks to top without removing
+    Rolling, // Add new tracks to top, dropping the oldest additions
+    Sync,    // Add new tracks, remove ones this config added that no longer match
+}
+
+/// Limits for the rolling update mode. Tracks are dropped, oldest addition
+/// first, until the playlist fits all of them.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RollingWindow {
+    /// Drop tracks added more than this many days ago
+    pub max_age_days: Option<u32>,
+    pub max_tracks: Option<usize>,
+    /// Cap on the playlist's total length
+    pub max_hours: Option<u32>,
 }
 
 /// Filter configuration for excluding tracks
@@ -29,7 +171,83 @@ pub enum UpdateMode {
 #[serde(rename_all = "camelCase")]
 pub struct FilterConfig {
     pub exclude_liked: bool,
+    /// Skip tracks on the ignore list of the review step
+    #[serde(default)]
+    pub exclude_ignored: bool,
     pub keyword_blacklist: Vec<String>,
+    /// Conditions tracks must meet, on top of the options above
+    #[serde(default)]
+    pub rules: Vec<FilterRule>,
+    #[serde(default)]
+    pub rule_match: RuleMatch,
+}
+
+/// How filter rules combine
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleMatch {
+    #[default]
+    All, // Keep tracks meeting every rule
+    Any, // Keep tracks meeting at least one rule
+}
+
+/// A condition a track has to meet to be kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FilterRule {
+    ReleaseYear {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    /// Inclusive YYYY-MM-DD bounds. Releases dated only by year or month
+    /// count as their first day.
+    ReleaseDate {
+        after: Option<String>,
+        before: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Duration {
+        min_ms: Option<u32>,
+        max_ms: Option<u32>,
+    },
+    /// Album types like album, single or compilation. An empty include list allows all.
+    AlbumType {
+        #[serde(default)]
+        include: Vec<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+    },
+    /// Keep tracks by at least one of these artists
+    ArtistAllow {
+        artists: Vec<String>,
+    },
+    /// Keep tracks by none of these artists
+    ArtistDeny {
+        artists: Vec<String>,
+    },
+    Regex {
+        field: TrackField,
+        pattern: String,
+        /// Keep tracks that don't match instead
+        #[serde(default)]
+        exclude: bool,
+    },
+    Explicit {
+        explicit: bool,
+    },
+    /// Tracks with unknown popularity (album and artist sources) always pass
+    Popularity {
+        min: Option<u32>,
+        max: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TrackField {
+    Name,
+    Artist,
+    Album,
 }
 
 /// Options for applying processing rules during update
@@ -47,6 +265,86 @@ pub struct ProcessingOptions {
     pub version_preference: String,
 }
 
+/// A source in a config's list, with its share of the sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceEntry {
+    #[serde(flatten)]
+    pub source: Source,
+    /// Fixed number of tracks to pick from this source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_size: Option<usize>,
+    /// Share of the config's `sample_total`, relative to the other sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    /// Picks from this source by rule, before sampling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<Selection>,
+}
+
+impl From<Source> for SourceEntry {
+    fn from(source: Source) -> Self {
+        Self {
+            source,
+            sample_size: None,
+            weight: None,
+            selection: None,
+        }
+    }
+}
+
+/// Picks tracks by rule instead of at random, e.g. the 50 newest or at most
+/// 2 per artist. Tracks are ordered with the sort rules, then kept in order
+/// while they fit within the caps.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Selection {
+    #[serde(default)]
+    pub order_by: Vec<crate::logic::SortRule>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub max_per_artist: Option<usize>,
+    #[serde(default)]
+    pub max_per_album: Option<usize>,
+}
+
+/// Where sampling gets its randomness from. Seeded sampling picks the same
+/// tracks again as long as the sources don't change.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SampleSeed {
+    #[default]
+    Random,
+    /// Same picks all day
+    Daily,
+    /// Same picks all week (ISO weeks)
+    Weekly,
+    Fixed {
+        value: u64,
+    },
+}
+
+impl SampleSeed {
+    /// Seed for a config's sampling on a given day, or None for random sampling
+    pub fn resolve(&self, config_id: &str, date: chrono::NaiveDate) -> Option<u64> {
+        let period = match self {
+            SampleSeed::Random => return None,
+            SampleSeed::Daily => date.format("%Y-%m-%d").to_string(),
+            SampleSeed::Weekly => date.format("%G-W%V").to_string(),
+            SampleSeed::Fixed { value } => return Some(*value),
+        };
+        Some(stable_hash(&format!("{}:{}", config_id, period)))
+    }
+}
+
+/// FNV-1a, so seeds stay the same across builds (unlike `DefaultHasher`)
+fn stable_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
 /// Full configuration for a dynamic playlist
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -54,13 +352,30 @@ pub struct DynamicPlaylistConfig {
     pub id: String,
     pub name: String, // Display name for the config
     pub target_playlist_id: String,
-    pub sources: Vec<Source>,
+    pub sources: Vec<SourceEntry>,
     pub filters: FilterConfig,
     pub update_mode: UpdateMode,
+    #[serde(default)]
+    pub rolling: RollingWindow,
     pub sample_per_source: Option<usize>,
+    /// Tracks to pick in total, split between sources by weight
+    #[serde(default)]
+    pub sample_total: Option<usize>,
+    #[serde(default)]
+    pub sample_seed: SampleSeed,
+    /// Picks from the combined tracks, before processing
+    #[serde(default)]
+    pub selection: Option<Selection>,
     pub include_liked_songs: bool,
     #[serde(default)]
     pub processing: ProcessingOptions,
+    /// Replaces the plain list of sources when set
+    #[serde(default)]
+    pub source_expression: Option<SourceExpr>,
+    /// ID the config had in the bundle it was imported from, so importing
+    /// the bundle again replaces it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_from: Option<String>,
 }
 
 impl DynamicPlaylistConfig {
@@ -72,10 +387,34 @@ impl DynamicPlaylistConfig {
             sources: Vec::new(),
             filters: FilterConfig::default(),
             update_mode: UpdateMode::Replace,
+            rolling: RollingWindow::default(),
             sample_per_source: None,
+            sample_total: None,
+            sample_seed: SampleSeed::default(),
+            selection: None,
             include_liked_songs: false,
             processing: ProcessingOptions::default(),
+            source_expression: None,
+            imported_from: None,
+        }
+    }
+
+    /// Sources in the list and in the source expression
+    pub fn all_sources(&self) -> Vec<&Source> {
+        let mut sources: Vec<&Source> = self.sources.iter().map(|e| &e.source).collect();
+        if let Some(expression) = &self.source_expression {
+            sources.extend(expression.sources());
+        }
+        sources
+    }
+
+    pub fn all_sources_mut(&mut self) -> Vec<&mut Source> {
+        let mut sources: Vec<&mut Source> =
+            self.sources.iter_mut().map(|e| &mut e.source).collect();
+        if let Some(expression) = &mut self.source_expression {
+            sources.extend(expression.sources_mut());
         }
+        sources
     }
 }
 
@@ -90,6 +429,11 @@ pub struct TrackInfo {
     pub album_type: String,
     pub release_date: String,
     pub duration_ms: u32,
+    #[serde(default)]
+    pub explicit: bool,
+    /// 0-100; not known for tracks from album listings
+    #[serde(default)]
+    pub popularity: Option<u32>,
 }
 
 // ... (skipping to line 291 in same file) or better to use separate chunks if far apart
@@ -106,9 +450,31 @@ impl TrackInfo {
             release_date: self.release_date.clone(),
             uri: self.uri.clone(),
             duration_ms: self.duration_ms,
+            popularity: self.popularity,
         }
     }
 
+    /// Convert a track from the Web API; local files and tracks without an ID give None
+    pub fn from_full_track(track: &rspotify::model::FullTrack) -> Option<Self> {
+        let id = track.id.as_ref()?;
+        Some(Self {
+            uri: id.uri(),
+            id: id.id().to_string(),
+            name: track.name.clone(),
+            artist: artist_names(&track.artists),
+            album: track.album.name.clone(),
+            album_type: track
+                .album
+                .album_type
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+            release_date: track.album.release_date.clone().unwrap_or_default(),
+            duration_ms: track.duration.num_milliseconds() as u32,
+            explicit: track.explicit,
+            popularity: Some(track.popularity),
+        })
+    }
+
     /// Convert from AppTrack back to TrackInfo
     pub fn from_app_track(track: &crate::logic::AppTrack) -> Self {
         Self {
@@ -120,59 +486,318 @@ impl TrackInfo {
             album_type: track.album_type.clone(),
             release_date: track.release_date.clone(),
             duration_ms: track.duration_ms,
+            explicit: false,
+            popularity: track.popularity,
         }
     }
 }
 
+/// Spotify's name for an album type, as tracks from the Web API carry it
+fn album_type_name(album_type: rspotify::model::AlbumType) -> &'static str {
+    use rspotify::model::AlbumType;
+    match album_type {
+        AlbumType::Album => "album",
+        AlbumType::Single => "single",
+        AlbumType::AppearsOn => "appears_on",
+        AlbumType::Compilation => "compilation",
+    }
+}
+
+/// All credited artists, joined the way playlist tracks are
+fn artist_names(artists: &[rspotify::model::SimplifiedArtist]) -> String {
+    artists
+        .iter()
+        .map(|a| a.name.clone())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub fn get_app_data_dir() -> PathBuf {
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("Spotify Sorter");
-    path
+    crate::profiles::current_profile_dir()
 }
 
 pub fn get_dynamic_configs_path() -> PathBuf {
     get_app_data_dir().join("dynamic_configs.json")
 }
 
-/// Load all saved dynamic playlist configurations
+const DYNAMIC_CONFIGS_FILE: ListFile = ListFile {
+    label: "dynamic configs",
+    migrations: &[crate::storage::unchanged],
+};
+
+/// Load all saved dynamic playlist configurations. Configs that fail to load
+/// are reported through `crate::storage::load_issues`.
 pub fn load_dynamic_configs() -> Vec<DynamicPlaylistConfig> {
-    let path = get_dynamic_configs_path();
-    if path.exists() {
-        match fs::read_to_string(&path) {
-            Ok(contents) => serde_json::from_str(&contents).unwrap_or_default(),
-            Err(_) => Vec::new(),
-        }
-    } else {
-        Vec::new()
-    }
+    DYNAMIC_CONFIGS_FILE.load(&get_dynamic_configs_path())
 }
 
 /// Save dynamic playlist configurations to disk
 pub fn save_dynamic_configs(configs: &[DynamicPlaylistConfig]) -> Result<(), String> {
-    let path = get_dynamic_configs_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let json = serde_json::to_string_pretty(configs).map_err(|e| e.to_string())?;
-    fs::write(&path, json).map_err(|e| e.to_string())?;
-    Ok(())
+    DYNAMIC_CONFIGS_FILE.save(&get_dynamic_configs_path(), configs)
 }
 
 /// Fetch tracks from a single source with rate limit handling
 pub async fn fetch_tracks_from_source(
-    spotify: &AuthCodeSpotify,
+    spotify: &SpotifyClient,
     source: &Source,
     app_handle: &tauri::AppHandle,
 ) -> Result<Vec<TrackInfo>, String> {
     match source {
-        Source::Playlist { id } => fetch_playlist_tracks(spotify, id, app_handle).await,
+        Source::Playlist { id } => {
+            let id = crate::spotify::parse_playlist_id(id)?;
+            fetch_playlist_tracks(spotify, &id, app_handle).await
+        }
         Source::LikedSongs => fetch_liked_songs(spotify).await,
+        Source::Artist {
+            id, include_groups, ..
+        } => {
+            let id = crate::spotify::parse_artist_id(id)?;
+            let (tracks, _) = fetch_artist_tracks(spotify, &id, include_groups, |_| true).await?;
+            Ok(tracks)
+        }
+        Source::SavedAlbums => fetch_saved_album_tracks(spotify).await,
+        Source::TopTracks { term } => fetch_top_tracks(spotify, *term).await,
+        Source::RecentlyPlayed => fetch_recently_played(spotify).await,
+        Source::DynamicConfig { .. } => {
+            Err("Dynamic playlist sources can only be used in a dynamic playlist".to_string())
+        }
+    }
+}
+
+/// Fail with a hint to log in again if the session predates a scope
+async fn require_scope(spotify: &SpotifyClient, scope: &str) -> Result<(), String> {
+    let needed = HashSet::from([scope.to_string()]);
+    if spotify.missing_scopes_of(&needed).await.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "This source needs the '{}' permission. Please log out and connect again.",
+            scope
+        ))
+    }
+}
+
+/// Albums seen per artist by earlier runs of each config, for new-release sources
+type ReleaseTracker = HashMap<String, HashMap<String, Vec<String>>>;
+
+fn get_release_tracker_path() -> PathBuf {
+    get_app_data_dir().join("release_tracker.json")
+}
+
+const RELEASE_TRACKER_FILE: DataFile = DataFile {
+    label: "release tracker",
+};
+
+fn load_release_tracker() -> ReleaseTracker {
+    RELEASE_TRACKER_FILE.load(&get_release_tracker_path())
+}
+
+fn save_release_tracker(tracker: &ReleaseTracker) -> Result<(), String> {
+    RELEASE_TRACKER_FILE.save(&get_release_tracker_path(), tracker)
+}
+
+/// Tracks each config put into its target and when (config id -> uri ->
+/// unix timestamp). Anything else in a target was added by hand.
+type Provenance = HashMap<String, HashMap<String, i64>>;
+
+fn get_provenance_path() -> PathBuf {
+    get_app_data_dir().join("track_additions.json")
+}
+
+const PROVENANCE_FILE: DataFile = DataFile {
+    label: "track additions",
+};
+
+fn load_provenance() -> Provenance {
+    PROVENANCE_FILE.load(&get_provenance_path())
+}
+
+fn save_provenance(provenance: &Provenance) -> Result<(), String> {
+    PROVENANCE_FILE.save(&get_provenance_path(), provenance)
+}
+
+/// Target contents for the rolling mode: new tracks go on top, then the
+/// oldest additions are dropped until the window's limits are met. Tracks
+/// already in the target that the config didn't add count as added now.
+/// Returns the uris in playlist order and the add times of the kept tracks.
+fn roll_tracks(
+    existing: Vec<TrackInfo>,
+    new_tracks: Vec<TrackInfo>,
+    added_at: &HashMap<String, i64>,
+    window: &RollingWindow,
+    now: i64,
+) -> (Vec<String>, HashMap<String, i64>) {
+    let existing_uris: HashSet<String> = existing.iter().map(|t| t.uri.clone()).collect();
+    let mut seen = HashSet::new();
+    let tracks: Vec<TrackInfo> = new_tracks
+        .into_iter()
+        .filter(|t| !existing_uris.contains(&t.uri))
+        .chain(existing)
+        .filter(|t| seen.insert(t.uri.clone()))
+        .collect();
+
+    let added = |uri: &str| added_at.get(uri).copied().unwrap_or(now);
+    let min_added = window
+        .max_age_days
+        .map(|days| now - days as i64 * 24 * 60 * 60);
+
+    // Newest additions claim the space first
+    let mut by_age: Vec<&TrackInfo> = tracks.iter().collect();
+    by_age.sort_by_key(|t| std::cmp::Reverse(added(&t.uri)));
+
+    let max_ms = window.max_hours.map(|h| h as u64 * 60 * 60 * 1000);
+    let mut total_ms = 0u64;
+    let mut kept = HashMap::new();
+    for track in by_age {
+        let track_added = added(&track.uri);
+        if min_added.is_some_and(|min| track_added < min)
+            || window.max_tracks.is_some_and(|max| kept.len() >= max)
+            || max_ms.is_some_and(|max| total_ms + track.duration_ms as u64 > max)
+        {
+            break;
+        }
+        total_ms += track.duration_ms as u64;
+        kept.insert(track.uri.clone(), track_added);
+    }
+
+    let uris = tracks
+        .into_iter()
+        .filter(|t| kept.contains_key(&t.uri))
+        .map(|t| t.uri)
+        .collect();
+    (uris, kept)
+}
+
+/// Tracks of an artist's releases that pass `keep`, plus the IDs of all
+/// releases found
+async fn fetch_artist_tracks(
+    spotify: &SpotifyClient,
+    artist_id: &str,
+    include_groups: &[String],
+    keep: impl Fn(&rspotify::model::SimplifiedAlbum) -> bool,
+) -> Result<(Vec<TrackInfo>, Vec<String>), String> {
+    use rspotify::model::{AlbumId, AlbumType, ArtistId};
+
+    let aid = ArtistId::from_id(artist_id).map_err(|e| format!("Invalid artist ID: {}", e))?;
+
+    let mut albums = Vec::new();
+    let mut seen_albums = HashSet::new();
+    for group in include_groups {
+        let album_type = match group.as_str() {
+            "album" => AlbumType::Album,
+            "single" => AlbumType::Single,
+            "appears_on" => AlbumType::AppearsOn,
+            "compilation" => AlbumType::Compilation,
+            other => return Err(format!("Unknown album group: {}", other)),
+        };
+
+        let mut offset = 0;
+        loop {
+            let page = spotify
+                .artist_albums_manual(aid.clone(), [album_type], None, Some(50), Some(offset))
+                .await
+                .map_err(|e| format!("Failed to fetch artist albums: {}", e))?;
+
+            for album in page.items {
+                if let Some(id) = album.id.as_ref().map(|id| id.id().to_string()) {
+                    if seen_albums.insert(id.clone()) {
+                        albums.push((id, album));
+                    }
+                }
+            }
+
+            if page.next.is_none() {
+                break;
+            }
+            offset += 50;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    let mut tracks = Vec::new();
+    for (album_id, album) in albums.iter().filter(|(_, album)| keep(album)) {
+        let alid = AlbumId::from_id(album_id).map_err(|e| format!("Invalid album ID: {}", e))?;
+        let mut offset = 0;
+        loop {
+            let page = spotify
+                .album_track_manual(alid.clone(), None, Some(50), Some(offset))
+                .await
+                .map_err(|e| format!("Failed to fetch album tracks: {}", e))?;
+
+            for track in page.items {
+                // Releases the artist only appears on also hold other artists' tracks
+                let by_artist = track
+                    .artists
+                    .iter()
+                    .any(|a| a.id.as_ref().is_some_and(|id| id.id() == artist_id));
+                if !by_artist {
+                    continue;
+                }
+
+                if let Some(id) = track.id.as_ref() {
+                    tracks.push(TrackInfo {
+                        uri: id.uri(),
+                        id: id.id().to_string(),
+                        name: track.name.clone(),
+                        artist: artist_names(&track.artists),
+                        album: album.name.clone(),
+                        album_type: album.album_type.clone().unwrap_or_default(),
+                        release_date: album.release_date.clone().unwrap_or_default(),
+                        duration_ms: track.duration.num_milliseconds() as u32,
+                        explicit: track.explicit,
+                        popularity: None,
+                    });
+                }
+            }
+
+            if page.next.is_none() {
+                break;
+            }
+            offset += 50;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    Ok((tracks, albums.into_iter().map(|(id, _)| id).collect()))
+}
+
+/// Tracks of an artist's releases that a config hasn't seen before
+async fn fetch_new_releases(
+    spotify: &SpotifyClient,
+    artist_id: &str,
+    include_groups: &[String],
+    seen: Option<&Vec<String>>,
+) -> Result<(Vec<TrackInfo>, Vec<String>), String> {
+    match seen {
+        Some(seen) => {
+            let seen: HashSet<&String> = seen.iter().collect();
+            fetch_artist_tracks(spotify, artist_id, include_groups, |album| {
+                album
+                    .id
+                    .as_ref()
+                    .is_some_and(|id| !seen.contains(&id.id().to_string()))
+            })
+            .await
+        }
+        None => {
+            let cutoff = (chrono::Local::now() - chrono::Duration::days(NEW_RELEASE_SEED_DAYS))
+                .format("%Y-%m-%d")
+                .to_string();
+            // Release dates may be just a year or year-month; those compare as older
+            fetch_artist_tracks(spotify, artist_id, include_groups, |album| {
+                album
+                    .release_date
+                    .as_deref()
+                    .is_some_and(|date| date.len() == 10 && date >= cutoff.as_str())
+            })
+            .await
+        }
     }
 }
 
 /// Fetch all tracks from a playlist
 async fn fetch_playlist_tracks(
-    spotify: &AuthCodeSpotify,
+    spotify: &SpotifyClient,
     playlist_id: &str,
     app_handle: &tauri::AppHandle,
 ) -> Result<Vec<TrackInfo>, String> {
@@ -189,10 +814,7 @@ async fn fetch_playlist_tracks(
         let mut loop_res = None;
 
         while attempts < 5 {
-            match spotify
-                .api_get(&url, &std::collections::HashMap::new())
-                .await
-            {
+            match spotify.api_get(&url, &HashMap::new()).await {
                 Ok(res_str) => {
                     loop_res = Some(res_str);
                     break;
@@ -226,7 +848,16 @@ async fn fetch_playlist_tracks(
             for item in items {
                 if let Some(track_val) = item["track"].as_object() {
                     if let Some(app_track) = crate::logic::AppTrack::from_json(track_val) {
-                        tracks.push(TrackInfo::from_app_track(&app_track));
+                        let mut track = TrackInfo::from_app_track(&app_track);
+                        track.explicit = track_val
+                            .get("explicit")
+                            .and_then(|e| e.as_bool())
+                            .unwrap_or(false);
+                        track.popularity = track_val
+                            .get("popularity")
+                            .and_then(|p| p.as_u64())
+                            .map(|p| p as u32);
+                        tracks.push(track);
                     }
                 }
             }
@@ -250,7 +881,7 @@ struct LikedSongsCacheEntry {
 }
 
 /// Fetch user's liked songs with Smart Caching
-async fn fetch_liked_songs(spotify: &AuthCodeSpotify) -> Result<Vec<TrackInfo>, String> {
+async fn fetch_liked_songs(spotify: &SpotifyClient) -> Result<Vec<TrackInfo>, String> {
     // 1. Light Check: Get total count (limit=1)
     let check_page = spotify
         .current_user_saved_tracks_manual(None, Some(1), Some(0))
@@ -294,39 +925,11 @@ async fn fetch_liked_songs(spotify: &AuthCodeSpotify) -> Result<Vec<TrackInfo>,
             .await
             .map_err(|e| format!("Failed to fetch liked songs: {}", e))?;
 
-        for item in page.items {
-            let track = item.track;
-            if let Some(uri) = track.id.as_ref().map(|id| id.uri()) {
-                // Handle optional artist/album
-                let artist_name = track
-                    .artists
-                    .first()
-                    .map(|a| a.name.clone())
-                    .unwrap_or_default();
-                let album_name = track.album.name.clone();
-                let album_type = track
-                    .album
-                    .album_type
-                    .as_ref()
-                    .map(|t| format!("{:?}", t))
-                    .unwrap_or_else(|| "unknown".to_string());
-                let release_date = track.album.release_date.clone().unwrap_or_default();
-
-                tracks.push(TrackInfo {
-                    uri: uri.clone(),
-                    id: track
-                        .id
-                        .map(|id| id.id().to_string())
-                        .unwrap_or_else(|| uri.clone()),
-                    name: track.name,
-                    artist: artist_name,
-                    album: album_name,
-                    album_type,
-                    release_date,
-                    duration_ms: track.duration.num_milliseconds() as u32,
-                });
-            }
-        }
+        tracks.extend(
+            page.items
+                .iter()
+                .filter_map(|item| TrackInfo::from_full_track(&item.track)),
+        );
 
         if page.next.is_none() {
             break;
@@ -350,177 +953,1614 @@ async fn fetch_liked_songs(spotify: &AuthCodeSpotify) -> Result<Vec<TrackInfo>,
     Ok(tracks)
 }
 
-/// Get set of liked song URIs for filtering
-pub async fn get_liked_song_uris(spotify: &AuthCodeSpotify) -> Result<HashSet<String>, String> {
-    let tracks = fetch_liked_songs(spotify).await?;
-    Ok(tracks.into_iter().map(|t| t.uri).collect())
-}
-
-/// Apply filters to a list of tracks
-pub fn apply_filters(
+#[derive(Serialize, Deserialize, Debug)]
+struct SavedAlbumsCacheEntry {
+    total_count: u32,
     tracks: Vec<TrackInfo>,
-    filters: &FilterConfig,
-    liked_uris: Option<&HashSet<String>>,
-) -> Vec<TrackInfo> {
-    tracks
-        .into_iter()
-        .filter(|track| {
-            // Exclude liked songs if configured
-            if filters.exclude_liked {
-                if let Some(liked) = liked_uris {
-                    if liked.contains(&track.uri) {
-                        return false;
-                    }
-                }
-            }
+    timestamp: i64,
+}
 
-            // Exclude by keyword blacklist
-            for keyword in &filters.keyword_blacklist {
-                let kw_lower = keyword.to_lowercase();
-                if track.name.to_lowercase().contains(&kw_lower)
-                    || track.artist.to_lowercase().contains(&kw_lower)
-                    || track.album.to_lowercase().contains(&kw_lower)
-                {
-                    return false;
-                }
-            }
+/// Fetch the tracks of all saved albums, cached like liked songs
+async fn fetch_saved_album_tracks(spotify: &SpotifyClient) -> Result<Vec<TrackInfo>, String> {
+    use rspotify::model::AlbumId;
 
-            true
-        })
-        .collect()
-}
+    let check_page = spotify
+        .current_user_saved_albums_manual(None, Some(1), Some(0))
+        .await
+        .map_err(|e| format!("Failed to check saved albums count: {}", e))?;
+    let remote_total = check_page.total;
 
-/// Sample tracks from a list (random selection)
-pub fn sample_tracks(tracks: Vec<TrackInfo>, max_count: Option<usize>) -> Vec<TrackInfo> {
-    match max_count {
-        Some(n) if n < tracks.len() => {
-            use rand::seq::SliceRandom;
-            let mut rng = rand::thread_rng();
-            let mut shuffled = tracks;
-            shuffled.shuffle(&mut rng);
-            shuffled.into_iter().take(n).collect()
+    let path = get_app_data_dir().join("saved_albums_cache.json");
+    if let Ok(content) = fs::read_to_string(&path) {
+        if let Ok(cache) = serde_json::from_str::<SavedAlbumsCacheEntry>(&content) {
+            let now = chrono::Utc::now().timestamp();
+            if cache.total_count == remote_total && (now - cache.timestamp) < 86400 {
+                println!("Saved albums Cache HIT: Count {} matches.", remote_total);
+                return Ok(cache.tracks);
+            }
         }
-        _ => tracks,
     }
-}
-
-/// Deduplicate tracks by URI
-pub fn deduplicate_tracks(tracks: Vec<TrackInfo>) -> Vec<TrackInfo> {
-    let mut seen = HashSet::new();
-    tracks
-        .into_iter()
-        .filter(|t| seen.insert(t.uri.clone()))
-        .collect()
-}
 
-/// Execute a full dynamic playlist update
-pub async fn update_dynamic_playlist(
-    spotify: &AuthCodeSpotify,
-    config: &DynamicPlaylistConfig,
-    app_handle: &tauri::AppHandle,
-) -> Result<usize, String> {
-    // Step 1: Collect tracks from all sources
-    let mut all_tracks = Vec::new();
+    println!(
+        "Saved albums Cache MISS (Remote: {}). Fetching full list...",
+        remote_total
+    );
 
-    for source in config.sources.iter() {
-        let mut source_tracks = fetch_tracks_from_source(spotify, source, app_handle).await?;
+    let mut tracks = Vec::new();
+    let mut offset = 0;
+    let limit = 50;
 
-        // Sample if configured
-        source_tracks = sample_tracks(source_tracks, config.sample_per_source);
+    loop {
+        let page = spotify
+            .current_user_saved_albums_manual(None, Some(limit), Some(offset))
+            .await
+            .map_err(|e| format!("Failed to fetch saved albums: {}", e))?;
+
+        for saved in &page.items {
+            let album = &saved.album;
+            let mut album_tracks = album.tracks.items.clone();
+
+            // Albums come with their first page of tracks; fetch the rest
+            let mut track_offset = album_tracks.len() as u32;
+            while track_offset < album.tracks.total {
+                let alid = AlbumId::from_id(album.id.id())
+                    .map_err(|e| format!("Invalid album ID: {}", e))?;
+                let more = spotify
+                    .album_track_manual(alid, None, Some(50), Some(track_offset))
+                    .await
+                    .map_err(|e| format!("Failed to fetch album tracks: {}", e))?;
+                if more.items.is_empty() {
+                    break;
+                }
+                track_offset += more.items.len() as u32;
+                album_tracks.extend(more.items);
+            }
 
-        all_tracks.extend(source_tracks);
+            for track in album_tracks {
+                if let Some(id) = track.id.as_ref() {
+                    tracks.push(TrackInfo {
+                        uri: id.uri(),
+                        id: id.id().to_string(),
+                        name: track.name.clone(),
+                        artist: artist_names(&track.artists),
+                        album: album.name.clone(),
+                        album_type: album_type_name(album.album_type).to_string(),
+                        release_date: album.release_date.clone(),
+                        duration_ms: track.duration.num_milliseconds() as u32,
+                        explicit: track.explicit,
+                        popularity: None,
+                    });
+                }
+            }
+        }
 
-        // Delay between sources
-        tokio::time::sleep(Duration::from_millis(200)).await;
-    }
+        if page.next.is_none() {
+            break;
+        }
+        offset += limit;
 
-    // Include liked songs if configured
-    if config.include_liked_songs {
-        let liked = fetch_liked_songs(spotify).await?;
-        let liked_sampled = sample_tracks(liked, config.sample_per_source);
-        all_tracks.extend(liked_sampled);
+        tokio::time::sleep(Duration::from_millis(50)).await;
     }
 
-    // Step 2: Get liked songs for filtering (if needed)
-    let liked_uris = if config.filters.exclude_liked {
-        Some(get_liked_song_uris(spotify).await?)
-    } else {
-        None
+    let entry = SavedAlbumsCacheEntry {
+        total_count: remote_total,
+        tracks: tracks.clone(),
+        timestamp: chrono::Utc::now().timestamp(),
+    };
+    if let Ok(json) = serde_json::to_string(&entry) {
+        let _ = fs::write(path, json);
+    }
+
+    Ok(tracks)
+}
+
+/// Top tracks per term. Spotify recomputes them about once a day, so there's
+/// no cheaper check than the cache's age.
+type TopTracksCache = HashMap<String, (Vec<TrackInfo>, i64)>;
+
+async fn fetch_top_tracks(
+    spotify: &SpotifyClient,
+    term: TopTracksTerm,
+) -> Result<Vec<TrackInfo>, String> {
+    use rspotify::model::TimeRange;
+
+    require_scope(spotify, "user-top-read").await?;
+
+    let key = format!("{:?}", term);
+    let path = get_app_data_dir().join("top_tracks_cache.json");
+    let mut cache: TopTracksCache = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    if let Some((tracks, timestamp)) = cache.get(&key) {
+        if (now - timestamp) < 86400 {
+            println!("Top tracks Cache HIT ({})", key);
+            return Ok(tracks.clone());
+        }
+    }
+
+    let time_range = match term {
+        TopTracksTerm::ShortTerm => TimeRange::ShortTerm,
+        TopTracksTerm::MediumTerm => TimeRange::MediumTerm,
+        TopTracksTerm::LongTerm => TimeRange::LongTerm,
     };
 
-    // Step 3: Apply filters
-    let filtered_tracks = apply_filters(all_tracks, &config.filters, liked_uris.as_ref());
+    let mut tracks = Vec::new();
+    let mut offset = 0;
+    let limit = 50;
 
-    // Step 4: Deduplicate (our basic dedup)
-    let unique_tracks = deduplicate_tracks(filtered_tracks);
+    loop {
+        let page = spotify
+            .current_user_top_tracks_manual(Some(time_range), Some(limit), Some(offset))
+            .await
+            .map_err(|e| format!("Failed to fetch top tracks: {}", e))?;
 
-    // Step 5: Apply processing options (sort/dupe using main app logic)
-    let processed_tracks = if config.processing.apply_sort || config.processing.apply_dupes {
-        // Convert to AppTrack for processing
-        let mut app_tracks: Vec<crate::logic::AppTrack> =
-            unique_tracks.iter().map(|t| t.to_app_track()).collect();
+        tracks.extend(page.items.iter().filter_map(TrackInfo::from_full_track));
 
-        // Apply sorting
-        if config.processing.apply_sort && !config.processing.sort_rules.is_empty() {
-            app_tracks = crate::logic::sort_tracks(app_tracks, &config.processing.sort_rules);
+        if page.next.is_none() || page.items.is_empty() {
+            break;
         }
+        offset += limit;
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    cache.insert(key, (tracks.clone(), now));
+    if let Ok(json) = serde_json::to_string(&cache) {
+        let _ = fs::write(path, json);
+    }
+
+    Ok(tracks)
+}
 
-        // Apply deduplication
-        if config.processing.apply_dupes && !config.processing.dupe_preference.is_empty() {
-            let (kept, _removed) =
-                crate::logic::remove_duplicates(app_tracks, &config.processing.dupe_preference);
-            app_tracks = kept;
+#[derive(Serialize, Deserialize, Debug)]
+struct RecentlyPlayedCacheEntry {
+    last_played_at: String,
+    tracks: Vec<TrackInfo>,
+}
+
+/// Fetch recently played tracks, newest first. Cached until something new is played.
+async fn fetch_recently_played(spotify: &SpotifyClient) -> Result<Vec<TrackInfo>, String> {
+    require_scope(spotify, "user-read-recently-played").await?;
+
+    // Light check: only the most recent play
+    let check_page = spotify
+        .current_user_recently_played(Some(1), None)
+        .await
+        .map_err(|e| format!("Failed to check recently played: {}", e))?;
+    let last_played_at = check_page
+        .items
+        .first()
+        .map(|item| item.played_at.to_rfc3339())
+        .unwrap_or_default();
+
+    let path = get_app_data_dir().join("recently_played_cache.json");
+    if let Ok(content) = fs::read_to_string(&path) {
+        if let Ok(cache) = serde_json::from_str::<RecentlyPlayedCacheEntry>(&content) {
+            if cache.last_played_at == last_played_at {
+                println!("Recently played Cache HIT: nothing played since last fetch.");
+                return Ok(cache.tracks);
+            }
         }
+    }
+
+    let page = spotify
+        .current_user_recently_played(Some(50), None)
+        .await
+        .map_err(|e| format!("Failed to fetch recently played: {}", e))?;
+    let tracks: Vec<TrackInfo> = page
+        .items
+        .iter()
+        .filter_map(|item| TrackInfo::from_full_track(&item.track))
+        .collect();
+
+    let entry = RecentlyPlayedCacheEntry {
+        last_played_at,
+        tracks: tracks.clone(),
+    };
+    if let Ok(json) = serde_json::to_string(&entry) {
+        let _ = fs::write(path, json);
+    }
+
+    Ok(tracks)
+}
+
+/// Get set of liked song URIs for filtering
+pub async fn get_liked_song_uris(spotify: &SpotifyClient) -> Result<HashSet<String>, String> {
+    let tracks = fetch_liked_songs(spotify).await?;
+    Ok(tracks.into_iter().map(|t| t.uri).collect())
+}
+
+/// Apply filters to a list of tracks
+pub fn apply_filters(
+    tracks: Vec<TrackInfo>,
+    filters: &FilterConfig,
+    liked_uris: Option<&HashSet<String>>,
+) -> Result<Vec<TrackInfo>, String> {
+    let rules = compile_rules(&filters.rules)?;
+
+    Ok(tracks
+        .into_iter()
+        .filter(|track| {
+            // Exclude liked songs if configured
+            if filters.exclude_liked {
+                if let Some(liked) = liked_uris {
+                    if liked.contains(&track.uri) {
+                        return false;
+                    }
+                }
+            }
+
+            // Exclude by keyword blacklist
+            for keyword in &filters.keyword_blacklist {
+                let kw_lower = keyword.to_lowercase();
+                if track.name.to_lowercase().contains(&kw_lower)
+                    || track.artist.to_lowercase().contains(&kw_lower)
+                    || track.album.to_lowercase().contains(&kw_lower)
+                {
+                    return false;
+                }
+            }
+
+            if rules.is_empty() {
+                return true;
+            }
+            match filters.rule_match {
+                RuleMatch::All => rules.iter().all(|rule| rule.matches(track)),
+                RuleMatch::Any => rules.iter().any(|rule| rule.matches(track)),
+            }
+        })
+        .collect())
+}
+
+/// A filter rule with its regex compiled
+struct CompiledRule<'a> {
+    rule: &'a FilterRule,
+    regex: Option<regex::Regex>,
+}
 
-        // Convert back to TrackInfo
-        app_tracks.iter().map(TrackInfo::from_app_track).collect()
+/// Check filter rules before saving, so a bad pattern doesn't fail every run
+pub fn validate_filters(filters: &FilterConfig) -> Result<(), String> {
+    compile_rules(&filters.rules).map(|_| ())
+}
+
+/// Compile filter rules, failing on invalid regex patterns
+fn compile_rules(rules: &[FilterRule]) -> Result<Vec<CompiledRule<'_>>, String> {
+    rules
+        .iter()
+        .map(|rule| {
+            let regex = match rule {
+                FilterRule::Regex { pattern, .. } => Some(
+                    regex::RegexBuilder::new(pattern)
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?,
+                ),
+                _ => None,
+            };
+            Ok(CompiledRule { rule, regex })
+        })
+        .collect()
+}
+
+/// Pad a YYYY or YYYY-MM release date to a full date, so dates compare as strings
+fn full_release_date(date: &str) -> String {
+    match date.len() {
+        4 => format!("{}-01-01", date),
+        7 => format!("{}-01", date),
+        _ => date.to_string(),
+    }
+}
+
+fn in_range<T: PartialOrd>(value: T, min: Option<T>, max: Option<T>) -> bool {
+    !matches!(min, Some(min) if value < min) && !matches!(max, Some(max) if value > max)
+}
+
+impl CompiledRule<'_> {
+    fn matches(&self, track: &TrackInfo) -> bool {
+        let artists = || {
+            track
+                .artist
+                .split(", ")
+                .map(|a| a.trim().to_lowercase())
+                .collect::<Vec<_>>()
+        };
+        let listed = |list: &[String]| {
+            let artists = artists();
+            list.iter()
+                .any(|name| artists.contains(&name.trim().to_lowercase()))
+        };
+
+        match self.rule {
+            FilterRule::ReleaseYear { min, max } => {
+                match track
+                    .release_date
+                    .get(..4)
+                    .and_then(|y| y.parse::<i32>().ok())
+                {
+                    Some(year) => in_range(year, *min, *max),
+                    None => false,
+                }
+            }
+            FilterRule::ReleaseDate { after, before } => {
+                if track.release_date.is_empty() {
+                    return false;
+                }
+                let date = full_release_date(&track.release_date);
+                in_range(
+                    date,
+                    after.as_deref().map(full_release_date),
+                    before.as_deref().map(full_release_date),
+                )
+            }
+            FilterRule::Duration { min_ms, max_ms } => {
+                in_range(track.duration_ms, *min_ms, *max_ms)
+            }
+            FilterRule::AlbumType { include, exclude } => {
+                let album_type = track.album_type.to_lowercase();
+                (include.is_empty() || include.iter().any(|t| t.to_lowercase() == album_type))
+                    && !exclude.iter().any(|t| t.to_lowercase() == album_type)
+            }
+            FilterRule::ArtistAllow { artists } => listed(artists),
+            FilterRule::ArtistDeny { artists } => !listed(artists),
+            FilterRule::Regex { field, exclude, .. } => {
+                let value = match field {
+                    TrackField::Name => &track.name,
+                    TrackField::Artist => &track.artist,
+                    TrackField::Album => &track.album,
+                };
+                let found = self.regex.as_ref().is_some_and(|re| re.is_match(value));
+                found != *exclude
+            }
+            FilterRule::Explicit { explicit } => track.explicit == *explicit,
+            FilterRule::Popularity { min, max } => match track.popularity {
+                Some(popularity) => in_range(popularity, *min, *max),
+                None => true,
+            },
+        }
+    }
+}
+
+/// Sample tracks from a list (random selection). With a seed the same
+/// input always gives the same sample.
+pub fn sample_tracks(
+    tracks: Vec<TrackInfo>,
+    max_count: Option<usize>,
+    seed: Option<u64>,
+) -> Vec<TrackInfo> {
+    match max_count {
+        Some(n) if n < tracks.len() => {
+            use rand::seq::SliceRandom;
+            use rand::SeedableRng;
+            let mut shuffled = tracks;
+            match seed {
+                Some(seed) => shuffled.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed)),
+                None => shuffled.shuffle(&mut rand::thread_rng()),
+            }
+            shuffled.into_iter().take(n).collect()
+        }
+        _ => tracks,
+    }
+}
+
+/// Apply a selection: order the tracks, then keep them while they fit the
+/// per-artist and per-album caps, up to the limit. Artists are counted by
+/// their first credited artist.
+pub fn select_tracks(tracks: Vec<TrackInfo>, selection: &Selection) -> Vec<TrackInfo> {
+    let ordered = if selection.order_by.is_empty() {
+        tracks
     } else {
-        unique_tracks
+        let app_tracks = tracks.iter().map(|t| t.to_app_track()).collect();
+        let sorted = crate::logic::sort_tracks(app_tracks, &selection.order_by);
+        let originals: HashMap<&str, &TrackInfo> =
+            tracks.iter().map(|t| (t.uri.as_str(), t)).collect();
+        sorted
+            .iter()
+            .map(|t| match originals.get(t.uri.as_str()) {
+                Some(original) => (*original).clone(),
+                None => TrackInfo::from_app_track(t),
+            })
+            .collect()
+    };
+
+    let mut per_artist: HashMap<String, usize> = HashMap::new();
+    let mut per_album: HashMap<String, usize> = HashMap::new();
+    let mut selected = Vec::new();
+
+    for track in ordered {
+        if selection.limit.is_some_and(|limit| selected.len() >= limit) {
+            break;
+        }
+        let artist = track
+            .artist
+            .split(", ")
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let album = format!("{}|{}", artist, track.album.to_lowercase());
+
+        let artist_count = per_artist.get(&artist).copied().unwrap_or(0);
+        let album_count = per_album.get(&album).copied().unwrap_or(0);
+        if selection
+            .max_per_artist
+            .is_some_and(|max| artist_count >= max)
+            || selection
+                .max_per_album
+                .is_some_and(|max| album_count >= max)
+        {
+            continue;
+        }
+
+        per_artist.insert(artist, artist_count + 1);
+        per_album.insert(album, album_count + 1);
+        selected.push(track);
+    }
+
+    selected
+}
+
+/// How many tracks to sample from each source: one per source in the list,
+/// or one for the source expression, followed by one for Liked Songs when
+/// `include_liked_songs` is set. Without `sample_total`, sources use their own
+/// size or `sample_per_source`.
+pub fn source_sample_sizes(config: &DynamicPlaylistConfig) -> Vec<Option<usize>> {
+    // (fixed size, weight) of each source
+    let mut entries: Vec<(Option<usize>, Option<f64>)> = match &config.source_expression {
+        Some(_) => vec![(None, None)],
+        None => config
+            .sources
+            .iter()
+            .map(|e| (e.sample_size, e.weight))
+            .collect(),
+    };
+    if config.include_liked_songs {
+        entries.push((None, None));
+    }
+
+    match config.sample_total {
+        Some(total) => split_sample(total, &entries)
+            .into_iter()
+            .map(Some)
+            .collect(),
+        None => entries
+            .iter()
+            .map(|(size, _)| size.or(config.sample_per_source))
+            .collect(),
+    }
+}
+
+/// Split `total` between sources given as (fixed size, weight). Fixed sizes
+/// come first; the rest is split between the other sources by weight (1 if
+/// unset), with rounding leftovers going to the largest remainders.
+fn split_sample(total: usize, entries: &[(Option<usize>, Option<f64>)]) -> Vec<usize> {
+    let fixed: usize = entries.iter().filter_map(|(size, _)| *size).sum();
+    let remaining = total.saturating_sub(fixed);
+    let weights: Vec<f64> = entries
+        .iter()
+        .map(|(size, weight)| {
+            if size.is_some() {
+                0.0
+            } else {
+                weight.unwrap_or(1.0).max(0.0)
+            }
+        })
+        .collect();
+    let weight_sum: f64 = weights.iter().sum();
+
+    let shares: Vec<f64> = weights
+        .iter()
+        .map(|w| {
+            if weight_sum > 0.0 {
+                remaining as f64 * w / weight_sum
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let mut sizes: Vec<usize> = shares.iter().map(|s| s.floor() as usize).collect();
+
+    let mut leftover = remaining.saturating_sub(sizes.iter().sum());
+    let mut by_remainder: Vec<usize> = (0..shares.len()).filter(|&i| weights[i] > 0.0).collect();
+    by_remainder.sort_by(|&a, &b| {
+        let ra = shares[a] - shares[a].floor();
+        let rb = shares[b] - shares[b].floor();
+        rb.partial_cmp(&ra).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for i in by_remainder {
+        if leftover == 0 {
+            break;
+        }
+        sizes[i] += 1;
+        leftover -= 1;
+    }
+
+    entries
+        .iter()
+        .zip(sizes)
+        .map(|((fixed, _), size)| fixed.unwrap_or(size))
+        .collect()
+}
+
+/// Deduplicate tracks by URI
+pub fn deduplicate_tracks(tracks: Vec<TrackInfo>) -> Vec<TrackInfo> {
+    let mut seen = HashSet::new();
+    tracks
+        .into_iter()
+        .filter(|t| seen.insert(t.uri.clone()))
+        .collect()
+}
+
+/// Results of configs computed during one run, by config ID, so a config
+/// feeding several others is only computed once
+pub type ComputedResults = HashMap<String, Vec<TrackInfo>>;
+
+/// What a config produces, before anything is written to Spotify
+struct ComputedConfig {
+    tracks: Vec<TrackInfo>,
+    seen_releases: Vec<(String, Vec<String>)>,
+    stats: Vec<SourceStats>,
+}
+
+/// Track counts of one source at each step of a config's pipeline. A track
+/// in several sources counts for the first one after deduplication.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceStats {
+    /// None for the combined result of a source expression
+    pub source: Option<Source>,
+    pub fetched: usize,
+    pub sampled: usize,
+    pub after_filters: usize,
+    pub after_dedupe: usize,
+    pub in_result: usize,
+}
+
+/// Fill in the counts after sampling from the uris each source contributed
+fn count_source_steps(
+    stats: &mut [SourceStats],
+    source_uris: &[Vec<String>],
+    filtered: &[TrackInfo],
+    unique: &[TrackInfo],
+    result: &[TrackInfo],
+) {
+    let filtered: HashSet<&str> = filtered.iter().map(|t| t.uri.as_str()).collect();
+    let mut first_source: HashMap<&str, usize> = HashMap::new();
+    for (index, uris) in source_uris.iter().enumerate() {
+        for uri in uris {
+            first_source.entry(uri.as_str()).or_insert(index);
+        }
+    }
+    let count_owned = |tracks: &[TrackInfo], index: usize| {
+        tracks
+            .iter()
+            .filter(|t| first_source.get(t.uri.as_str()) == Some(&index))
+            .count()
+    };
+
+    for (index, stat) in stats.iter_mut().enumerate() {
+        stat.after_filters = source_uris[index]
+            .iter()
+            .filter(|uri| filtered.contains(uri.as_str()))
+            .count();
+        stat.after_dedupe = count_owned(unique, index);
+        stat.in_result = count_owned(result, index);
+    }
+}
+
+/// Order configs so each comes after the dynamic configs it uses as sources.
+/// Configs in a cycle, or depending on one, are returned separately.
+pub fn order_by_dependencies(
+    configs: &[DynamicPlaylistConfig],
+) -> (Vec<&DynamicPlaylistConfig>, Vec<&DynamicPlaylistConfig>) {
+    let dependencies = |config: &DynamicPlaylistConfig| -> Vec<String> {
+        config
+            .all_sources()
+            .into_iter()
+            .filter_map(|source| match source {
+                Source::DynamicConfig { id } if configs.iter().any(|c| c.id == *id) => {
+                    Some(id.clone())
+                }
+                _ => None,
+            })
+            .collect()
+    };
+
+    let mut ordered = Vec::new();
+    let mut done: HashSet<String> = HashSet::new();
+    let mut remaining: Vec<&DynamicPlaylistConfig> = configs.iter().collect();
+
+    loop {
+        let (ready, blocked): (Vec<_>, Vec<_>) = remaining
+            .into_iter()
+            .partition(|c| dependencies(c).iter().all(|id| done.contains(id)));
+        if ready.is_empty() {
+            return (ordered, blocked);
+        }
+        done.extend(ready.iter().map(|c| c.id.clone()));
+        ordered.extend(ready);
+        remaining = blocked;
+    }
+}
+
+fn cycle_error(chain: &[String], id: &str) -> String {
+    let configs = load_dynamic_configs();
+    let name = |id: &str| {
+        configs
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| id.to_string())
     };
+    let start = chain.iter().position(|c| c == id).unwrap_or(0);
+    let mut names: Vec<String> = chain[start..].iter().map(|c| name(c)).collect();
+    names.push(name(id));
+    format!(
+        "Dynamic playlists feed into each other: {}",
+        names.join(" → ")
+    )
+}
+
+/// State shared by the sources of a config while it's computed
+struct SourceContext<'a> {
+    spotify: &'a SpotifyClient,
+    config: &'a DynamicPlaylistConfig,
+    app_handle: &'a tauri::AppHandle,
+    computed: &'a mut ComputedResults,
+    chain: &'a mut Vec<String>,
+    release_tracker: ReleaseTracker,
+    seen_releases: Vec<(String, Vec<String>)>,
+}
+
+/// Fetch one source of a config, computing dynamic config sources and
+/// tracking new releases on the way
+async fn fetch_config_source(
+    ctx: &mut SourceContext<'_>,
+    source: &Source,
+) -> Result<Vec<TrackInfo>, String> {
+    match source {
+        Source::DynamicConfig { id } => {
+            if ctx.chain.contains(id) {
+                return Err(cycle_error(ctx.chain, id));
+            }
+            if let Some(tracks) = ctx.computed.get(id) {
+                return Ok(tracks.clone());
+            }
+            let configs = load_dynamic_configs();
+            let dependency = configs
+                .iter()
+                .find(|c| c.id == *id)
+                .ok_or_else(|| format!("Source dynamic playlist {} not found", id))?;
+            let result = compute_dynamic_tracks(
+                ctx.spotify,
+                dependency,
+                ctx.app_handle,
+                ctx.computed,
+                ctx.chain,
+            )
+            .await?;
+            ctx.computed.insert(id.clone(), result.tracks.clone());
+            // The dependency's new releases only count as seen once its own
+            // target gets them, so they aren't saved with this config
+            Ok(result.tracks)
+        }
+        Source::Artist {
+            id,
+            include_groups,
+            new_releases_only: true,
+        } => {
+            let artist_id = crate::spotify::parse_artist_id(id)?;
+            let seen = ctx
+                .release_tracker
+                .get(&ctx.config.id)
+                .and_then(|artists| artists.get(&artist_id));
+            let (tracks, albums) =
+                fetch_new_releases(ctx.spotify, &artist_id, include_groups, seen).await?;
+            ctx.seen_releases.push((artist_id, albums));
+            Ok(tracks)
+        }
+        _ => fetch_tracks_from_source(ctx.spotify, source, ctx.app_handle).await,
+    }
+}
+
+/// Evaluate a source expression, keeping the order tracks first appear in
+fn evaluate_expression<'a, 'b: 'a>(
+    ctx: &'a mut SourceContext<'b>,
+    expression: &'a SourceExpr,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<TrackInfo>, String>> + Send + 'a>>
+{
+    Box::pin(async move {
+        match expression {
+            SourceExpr::Source { source } => {
+                let tracks = fetch_config_source(ctx, source).await?;
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok(tracks)
+            }
+            SourceExpr::Union { items, identity } => {
+                let mut seen = HashSet::new();
+                let mut result = Vec::new();
+                for item in items {
+                    for track in evaluate_expression(ctx, item).await? {
+                        if seen.insert(identity.key(&track)) {
+                            result.push(track);
+                        }
+                    }
+                }
+                Ok(result)
+            }
+            SourceExpr::Intersection { items, identity } => {
+                let Some((first, others)) = items.split_first() else {
+                    return Ok(Vec::new());
+                };
+                let mut result = evaluate_expression(ctx, first).await?;
+                for item in others {
+                    let keys = identity.keys(&evaluate_expression(ctx, item).await?);
+                    result.retain(|track| keys.contains(&identity.key(track)));
+                }
+                Ok(result)
+            }
+            SourceExpr::Difference {
+                from,
+                subtract,
+                identity,
+            } => {
+                let mut result = evaluate_expression(ctx, from).await?;
+                for item in subtract {
+                    let keys = identity.keys(&evaluate_expression(ctx, item).await?);
+                    result.retain(|track| !keys.contains(&identity.key(track)));
+                }
+                Ok(result)
+            }
+        }
+    })
+}
+
+/// Collect, filter and process a config's tracks. Dynamic config sources are
+/// computed on the way, reusing results already in `computed`.
+fn compute_dynamic_tracks<'a>(
+    spotify: &'a SpotifyClient,
+    config: &'a DynamicPlaylistConfig,
+    app_handle: &'a tauri::AppHandle,
+    computed: &'a mut ComputedResults,
+    chain: &'a mut Vec<String>,
+) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ComputedConfig, String>> + Send + 'a>>
+{
+    Box::pin(async move {
+        chain.push(config.id.clone());
+
+        // Step 1: Collect tracks from all sources
+        let mut ctx = SourceContext {
+            spotify,
+            config,
+            app_handle,
+            computed,
+            chain,
+            release_tracker: load_release_tracker(),
+            seen_releases: Vec::new(),
+        };
+        let mut all_tracks = Vec::new();
+        let mut stats = Vec::new();
+        let mut source_uris = Vec::new();
+        let mut add_source = |source: Option<Source>, fetched: usize, tracks: &[TrackInfo]| {
+            stats.push(SourceStats {
+                source,
+                fetched,
+                sampled: tracks.len(),
+                after_filters: 0,
+                after_dedupe: 0,
+                in_result: 0,
+            });
+            source_uris.push(tracks.iter().map(|t| t.uri.clone()).collect::<Vec<_>>());
+        };
+        let seed = config
+            .sample_seed
+            .resolve(&config.id, chrono::Local::now().date_naive());
+        // Each source gets its own stream, so one source changing doesn't reshuffle the others
+        let source_seed = |index: usize| seed.map(|s| s ^ stable_hash(&index.to_string()));
+
+        let sizes = source_sample_sizes(config);
+        match &config.source_expression {
+            // Sampling individual sources would skew set operations, so the
+            // limit applies to the expression's result
+            Some(expression) => {
+                let tracks = evaluate_expression(&mut ctx, expression).await?;
+                let fetched = tracks.len();
+                let sampled = sample_tracks(tracks, sizes[0], seed);
+                add_source(None, fetched, &sampled);
+                all_tracks.extend(sampled);
+            }
+            None => {
+                for (index, entry) in config.sources.iter().enumerate() {
+                    let mut source_tracks = fetch_config_source(&mut ctx, &entry.source).await?;
+                    let fetched = source_tracks.len();
+
+                    if let Some(selection) = &entry.selection {
+                        source_tracks = select_tracks(source_tracks, selection);
+                    }
+
+                    // Sample if configured
+                    source_tracks = sample_tracks(source_tracks, sizes[index], source_seed(index));
+                    add_source(Some(entry.source.clone()), fetched, &source_tracks);
+
+                    all_tracks.extend(source_tracks);
+
+                    // Delay between sources
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+            }
+        }
+        let SourceContext {
+            chain,
+            seen_releases,
+            ..
+        } = ctx;
+
+        // Include liked songs if configured
+        if config.include_liked_songs {
+            let liked = fetch_liked_songs(spotify).await?;
+            let fetched = liked.len();
+            // Liked Songs always has the last sample size
+            let liked_sampled = sample_tracks(
+                liked,
+                sizes.last().copied().flatten(),
+                source_seed(config.sources.len()),
+            );
+            add_source(Some(Source::LikedSongs), fetched, &liked_sampled);
+            all_tracks.extend(liked_sampled);
+        }
+
+        // Step 2: Get liked songs for filtering (if needed)
+        let liked_uris = if config.filters.exclude_liked {
+            Some(get_liked_song_uris(spotify).await?)
+        } else {
+            None
+        };
+
+        // Step 3: Apply filters
+        let mut filtered_tracks = apply_filters(all_tracks, &config.filters, liked_uris.as_ref())?;
+        if config.filters.exclude_ignored {
+            let ignored = crate::commands::ignored_track_uris();
+            filtered_tracks.retain(|t| !ignored.contains(&t.uri));
+        }
+        let bans = crate::bans::load_ban_list();
+        filtered_tracks.retain(|t| !bans.bans(&t.uri, &t.artist, &t.album));
+
+        // Step 4: Deduplicate (our basic dedup)
+        let mut unique_tracks = deduplicate_tracks(filtered_tracks.clone());
+        let deduped_tracks = unique_tracks.clone();
+
+        if let Some(selection) = &config.selection {
+            unique_tracks = select_tracks(unique_tracks, selection);
+        }
+
+        // Step 5: Apply processing options (sort/dupe using main app logic)
+        let apply_versions =
+            config.processing.apply_versions && !config.processing.version_preference.is_empty();
+        let processed_tracks = if config.processing.apply_sort
+            || config.processing.apply_dupes
+            || apply_versions
+        {
+            // Convert to AppTrack for processing
+            let mut app_tracks: Vec<crate::logic::AppTrack> =
+                unique_tracks.iter().map(|t| t.to_app_track()).collect();
+
+            // Swap in better versions, before sorting like the main scan does
+            if apply_versions {
+                let mut search_cache = crate::versions::SearchCache::load();
+                for track in app_tracks.iter_mut() {
+                    if let Some(best) = crate::versions::find_better_version(
+                        spotify,
+                        app_handle,
+                        track,
+                        &config.processing.version_preference,
+                        &mut search_cache,
+                        &bans,
+                    )
+                    .await
+                    {
+                        *track = best;
+                    }
+                }
+                search_cache.save()?;
+            }
+
+            // Apply sorting
+            if config.processing.apply_sort && !config.processing.sort_rules.is_empty() {
+                app_tracks = crate::logic::sort_tracks(app_tracks, &config.processing.sort_rules);
+            }
+
+            // Apply deduplication
+            if config.processing.apply_dupes && !config.processing.dupe_preference.is_empty() {
+                let (kept, _removed) =
+                    crate::logic::remove_duplicates(app_tracks, &config.processing.dupe_preference);
+                app_tracks = kept;
+            }
+
+            // Convert back to TrackInfo, keeping what AppTrack doesn't carry
+            let originals: HashMap<&str, &TrackInfo> =
+                unique_tracks.iter().map(|t| (t.uri.as_str(), t)).collect();
+            app_tracks
+                .iter()
+                .map(|t| match originals.get(t.uri.as_str()) {
+                    Some(original) => (*original).clone(),
+                    None => TrackInfo::from_app_track(t),
+                })
+                .collect()
+        } else {
+            unique_tracks
+        };
+
+        count_source_steps(
+            &mut stats,
+            &source_uris,
+            &filtered_tracks,
+            &deduped_tracks,
+            &processed_tracks,
+        );
+
+        chain.pop();
+        Ok(ComputedConfig {
+            tracks: processed_tracks,
+            seen_releases,
+            stats,
+        })
+    })
+}
+
+/// Outcome of a dynamic playlist update
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicUpdateResult {
+    pub track_count: usize,
+    /// URIs put into the target by this update
+    pub added: Vec<String>,
+    /// URIs taken out of the target by this update
+    pub removed: Vec<String>,
+}
+
+impl DynamicUpdateResult {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} tracks (+{}, -{})",
+            self.track_count,
+            self.added.len(),
+            self.removed.len()
+        )
+    }
+}
+
+/// Everything an update would do, worked out without writing anything
+struct UpdatePlan {
+    result: DynamicUpdateResult,
+    final_uris: Vec<String>,
+    existing: Vec<TrackInfo>,
+    processed: Vec<TrackInfo>,
+    /// Provenance of the config's target after the update
+    owned: HashMap<String, i64>,
+    seen_releases: Vec<(String, Vec<String>)>,
+    stats: Vec<SourceStats>,
+}
+
+impl UpdatePlan {
+    /// Tracks of the target or the config's result, by URI
+    fn tracks(&self, uris: &[String]) -> Vec<TrackInfo> {
+        let mut by_uri: HashMap<&str, &TrackInfo> = HashMap::new();
+        for track in self.existing.iter().chain(self.processed.iter()) {
+            by_uri.entry(track.uri.as_str()).or_insert(track);
+        }
+        uris.iter()
+            .filter_map(|uri| by_uri.get(uri.as_str()).map(|t| (*t).clone()))
+            .collect()
+    }
+}
+
+/// Compute a config's tracks and the target's contents after the update
+async fn plan_update(
+    spotify: &SpotifyClient,
+    config: &DynamicPlaylistConfig,
+    app_handle: &tauri::AppHandle,
+    computed: &mut ComputedResults,
+) -> Result<UpdatePlan, String> {
+    let ComputedConfig {
+        tracks: processed_tracks,
+        seen_releases,
+        stats,
+    } = compute_dynamic_tracks(spotify, config, app_handle, computed, &mut Vec::new()).await?;
+    computed.insert(config.id.clone(), processed_tracks.clone());
+
+    let owned = load_provenance().remove(&config.id).unwrap_or_default();
+    let now = chrono::Utc::now().timestamp();
+
+    let existing = fetch_playlist_tracks(spotify, &config.target_playlist_id, app_handle).await?;
+    let existing_uris: Vec<String> = existing.iter().map(|t| t.uri.clone()).collect();
+    let existing_set: HashSet<&str> = existing_uris.iter().map(|u| u.as_str()).collect();
+    let new_tracks: Vec<&TrackInfo> = processed_tracks
+        .iter()
+        .filter(|t| !existing_set.contains(t.uri.as_str()))
+        .collect();
 
     // Step 6: Generate final URI list
+    let mut rolled = None;
     let final_uris: Vec<String> = match config.update_mode {
         UpdateMode::Replace => processed_tracks.iter().map(|t| t.uri.clone()).collect(),
 
         UpdateMode::Merge => {
-            let existing =
-                fetch_playlist_tracks(spotify, &config.target_playlist_id, app_handle).await?;
-            let mut combined = existing;
-            combined.extend(processed_tracks);
+            let mut combined = existing.clone();
+            combined.extend(new_tracks.iter().map(|t| (*t).clone()));
             let deduped = deduplicate_tracks(combined);
             deduped.iter().map(|t| t.uri.clone()).collect()
         }
 
-        UpdateMode::Append => {
-            let existing =
-                fetch_playlist_tracks(spotify, &config.target_playlist_id, app_handle).await?;
-            let existing_uris: HashSet<_> = existing.iter().map(|t| t.uri.clone()).collect();
+        // Prepend new tracks to existing
+        UpdateMode::Append => new_tracks
+            .iter()
+            .map(|t| t.uri.clone())
+            .chain(existing_uris.iter().cloned())
+            .collect(),
+
+        UpdateMode::Rolling => {
+            let (uris, kept) = roll_tracks(
+                existing.clone(),
+                processed_tracks.clone(),
+                &owned,
+                &config.rolling,
+                now,
+            );
+            rolled = Some(kept);
+            uris
+        }
 
-            // Only add truly new tracks
-            let new_only: Vec<_> = processed_tracks
-                .into_iter()
-                .filter(|t| !existing_uris.contains(&t.uri))
-                .map(|t| t.uri)
-                .collect();
+        // Keep hand-added tracks and owned tracks that still match
+        UpdateMode::Sync => {
+            let matching: HashSet<&str> = processed_tracks.iter().map(|t| t.uri.as_str()).collect();
+            existing_uris
+                .iter()
+                .filter(|uri| !owned.contains_key(*uri) || matching.contains(uri.as_str()))
+                .cloned()
+                .chain(new_tracks.iter().map(|t| t.uri.clone()))
+                .collect()
+        }
+    };
+
+    let final_set: HashSet<&str> = final_uris.iter().map(|u| u.as_str()).collect();
+    let result = DynamicUpdateResult {
+        track_count: final_uris.len(),
+        added: final_uris
+            .iter()
+            .filter(|uri| !existing_set.contains(uri.as_str()))
+            .cloned()
+            .collect(),
+        removed: existing_uris
+            .iter()
+            .filter(|uri| !final_set.contains(uri.as_str()))
+            .cloned()
+            .collect(),
+    };
 
-            // Prepend new tracks to existing
-            let mut result = new_only;
-            result.extend(existing.into_iter().map(|t| t.uri));
-            result
+    // Which tracks in the target came from this config
+    let now_owned = match rolled {
+        Some(kept) => kept,
+        None => {
+            let added: HashSet<&str> = result.added.iter().map(|u| u.as_str()).collect();
+            final_uris
+                .iter()
+                .filter_map(|uri| match owned.get(uri) {
+                    Some(time) => Some((uri.clone(), *time)),
+                    None if added.contains(uri.as_str())
+                        || config.update_mode == UpdateMode::Replace =>
+                    {
+                        Some((uri.clone(), now))
+                    }
+                    None => None,
+                })
+                .collect()
         }
     };
 
-    let track_count = final_uris.len();
+    Ok(UpdatePlan {
+        result,
+        final_uris,
+        existing,
+        processed: processed_tracks,
+        owned: now_owned,
+        seen_releases,
+        stats,
+    })
+}
+
+/// Execute a full dynamic playlist update
+pub async fn update_dynamic_playlist(
+    spotify: &SpotifyClient,
+    config: &DynamicPlaylistConfig,
+    app_handle: &tauri::AppHandle,
+) -> Result<DynamicUpdateResult, String> {
+    update_dynamic_playlist_in_run(spotify, config, app_handle, &mut ComputedResults::new()).await
+}
+
+/// Update a config as part of a run over several configs. Its result is added
+/// to `computed` for configs that use it as a source.
+pub async fn update_dynamic_playlist_in_run(
+    spotify: &SpotifyClient,
+    config: &DynamicPlaylistConfig,
+    app_handle: &tauri::AppHandle,
+    computed: &mut ComputedResults,
+) -> Result<DynamicUpdateResult, String> {
+    let plan = plan_update(spotify, config, app_handle, computed).await?;
+
+    // Back up the target first, so the run can be undone from the history
+    let unchanged = plan
+        .existing
+        .iter()
+        .map(|t| &t.uri)
+        .eq(plan.final_uris.iter());
+    let backup_file = if unchanged {
+        None
+    } else {
+        Some(crate::commands::backup_dynamic_target(
+            config,
+            &plan.existing,
+        )?)
+    };
 
     // Step 7: Update the playlist
     crate::spotify::update_playlist_items(
         spotify,
         &config.target_playlist_id,
-        final_uris,
+        plan.final_uris.clone(),
         None,
         app_handle,
     )
     .await?;
 
-    Ok(track_count)
+    let added = plan.tracks(&plan.result.added);
+    let removed = plan.tracks(&plan.result.removed);
+
+    let mut provenance = load_provenance();
+    provenance.insert(config.id.clone(), plan.owned);
+    save_provenance(&provenance)?;
+
+    // Only remember releases once they made it into the playlist
+    if !plan.seen_releases.is_empty() {
+        let mut release_tracker = load_release_tracker();
+        let artists = release_tracker.entry(config.id.clone()).or_default();
+        for (artist_id, albums) in plan.seen_releases {
+            artists.insert(artist_id, albums);
+        }
+        save_release_tracker(&release_tracker)?;
+    }
+
+    // The playlist already changed, so a failed history entry doesn't fail the run
+    if let Some(backup_file) = backup_file {
+        if let Err(e) =
+            crate::commands::log_dynamic_run(app_handle, config, backup_file, &added, &removed)
+        {
+            println!("Failed to add '{}' run to history: {}", config.name, e);
+        }
+    }
+
+    Ok(plan.result)
+}
+
+/// What an update would do to the target, for checking a config
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicPreview {
+    /// The target's tracks after the update, in order
+    pub tracks: Vec<TrackInfo>,
+    pub added: Vec<TrackInfo>,
+    pub removed: Vec<TrackInfo>,
+    /// Tracks that stay but change position relative to each other
+    pub moved: Vec<TrackInfo>,
+    pub sources: Vec<SourceStats>,
+}
+
+/// Run the update pipeline without writing to Spotify or saving any state
+pub async fn preview_dynamic_playlist(
+    spotify: &SpotifyClient,
+    config: &DynamicPlaylistConfig,
+    app_handle: &tauri::AppHandle,
+) -> Result<DynamicPreview, String> {
+    let plan = plan_update(spotify, config, app_handle, &mut ComputedResults::new()).await?;
+
+    // Compare the order of tracks kept from before
+    let final_set: HashSet<&str> = plan.final_uris.iter().map(|u| u.as_str()).collect();
+    let existing_set: HashSet<&str> = plan.existing.iter().map(|t| t.uri.as_str()).collect();
+    let kept_before: Vec<&str> = plan
+        .existing
+        .iter()
+        .map(|t| t.uri.as_str())
+        .filter(|uri| final_set.contains(uri))
+        .collect();
+    let kept_after: Vec<&str> = plan
+        .final_uris
+        .iter()
+        .map(|u| u.as_str())
+        .filter(|uri| existing_set.contains(uri))
+        .collect();
+    let moved = moved_tracks(&kept_before, &kept_after);
+
+    Ok(DynamicPreview {
+        tracks: plan.tracks(&plan.final_uris),
+        added: plan.tracks(&plan.result.added),
+        removed: plan.tracks(&plan.result.removed),
+        moved: plan.tracks(&moved),
+        sources: plan.stats,
+    })
+}
+
+/// Tracks that changed position between two orders of the same tracks:
+/// those outside the longest run that kept its relative order, so moving
+/// one track doesn't count every track it jumped over
+fn moved_tracks(before: &[&str], after: &[&str]) -> Vec<String> {
+    // Old position of each track, taking repeats of a URI in order
+    let mut positions: HashMap<&str, std::collections::VecDeque<usize>> = HashMap::new();
+    for (i, uri) in before.iter().enumerate() {
+        positions.entry(uri).or_default().push_back(i);
+    }
+    let old_index: Vec<usize> = after
+        .iter()
+        .map(|uri| {
+            positions
+                .get_mut(uri)
+                .and_then(|p| p.pop_front())
+                .unwrap_or(usize::MAX)
+        })
+        .collect();
+
+    // Longest increasing subsequence of the old positions
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; old_index.len()];
+    for (i, &index) in old_index.iter().enumerate() {
+        let len = tails.partition_point(|&t| old_index[t] < index);
+        previous[i] = len.checked_sub(1).map(|l| tails[l]);
+        if len == tails.len() {
+            tails.push(i);
+        } else {
+            tails[len] = i;
+        }
+    }
+    let mut in_order = vec![false; old_index.len()];
+    let mut next = tails.last().copied();
+    while let Some(i) = next {
+        in_order[i] = true;
+        next = previous[i];
+    }
+
+    after
+        .iter()
+        .zip(in_order)
+        .filter(|(_, kept)| !kept)
+        .map(|(uri, _)| uri.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sample_size: Option<usize>, weight: Option<f64>) -> SourceEntry {
+        SourceEntry {
+            sample_size,
+            weight,
+            ..SourceEntry::from(Source::LikedSongs)
+        }
+    }
+
+    fn config_with(sources: Vec<SourceEntry>, total: Option<usize>) -> DynamicPlaylistConfig {
+        let mut config = DynamicPlaylistConfig::new("Test".to_string(), "target".to_string());
+        config.sources = sources;
+        config.sample_total = total;
+        config
+    }
+
+    #[test]
+    fn sample_sizes_fixed_sizes_come_before_weights() {
+        let config = config_with(
+            vec![
+                entry(Some(10), None),
+                entry(None, Some(2.0)),
+                entry(None, Some(1.0)),
+            ],
+            Some(40),
+        );
+        assert_eq!(
+            source_sample_sizes(&config),
+            vec![Some(10), Some(20), Some(10)]
+        );
+    }
+
+    #[test]
+    fn sample_sizes_leftovers_go_to_largest_remainders() {
+        let config = config_with(
+            vec![entry(None, None), entry(None, None), entry(None, None)],
+            Some(10),
+        );
+        let sizes = source_sample_sizes(&config);
+        assert_eq!(sizes.iter().flatten().sum::<usize>(), 10);
+        assert_eq!(sizes, vec![Some(4), Some(3), Some(3)]);
+
+        let config = config_with(
+            vec![entry(None, Some(1.0)), entry(None, Some(2.0))],
+            Some(10),
+        );
+        assert_eq!(source_sample_sizes(&config), vec![Some(3), Some(7)]);
+    }
+
+    #[test]
+    fn sample_sizes_zero_weights_get_nothing() {
+        let config = config_with(
+            vec![entry(None, Some(0.0)), entry(None, Some(1.0))],
+            Some(5),
+        );
+        assert_eq!(source_sample_sizes(&config), vec![Some(0), Some(5)]);
+
+        let config = config_with(
+            vec![entry(None, Some(0.0)), entry(None, Some(0.0))],
+            Some(5),
+        );
+        assert_eq!(source_sample_sizes(&config), vec![Some(0), Some(0)]);
+    }
+
+    #[test]
+    fn sample_sizes_total_smaller_than_fixed_sizes() {
+        let config = config_with(
+            vec![
+                entry(Some(10), None),
+                entry(Some(5), None),
+                entry(None, None),
+            ],
+            Some(8),
+        );
+        assert_eq!(
+            source_sample_sizes(&config),
+            vec![Some(10), Some(5), Some(0)]
+        );
+    }
+
+    #[test]
+    fn sample_sizes_include_liked_songs_in_the_total() {
+        let mut config = config_with(vec![entry(None, None)], Some(10));
+        config.include_liked_songs = true;
+        assert_eq!(source_sample_sizes(&config), vec![Some(5), Some(5)]);
+
+        config.sample_total = None;
+        config.sample_per_source = Some(3);
+        assert_eq!(source_sample_sizes(&config), vec![Some(3), Some(3)]);
+    }
+
+    #[test]
+    fn moving_one_track_only_reports_that_track() {
+        let before = ["a", "b", "c", "d", "e"];
+        assert_eq!(moved_tracks(&before, &["b", "c", "d", "e", "a"]), vec!["a"]);
+        assert_eq!(moved_tracks(&before, &["a", "d", "b", "c", "e"]), vec!["d"]);
+        assert!(moved_tracks(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn moved_tracks_handles_repeats_and_reversal() {
+        assert_eq!(moved_tracks(&["a", "b", "a"], &["a", "a", "b"]), vec!["a"]);
+        assert_eq!(moved_tracks(&["a", "b", "c"], &["c", "b", "a"]).len(), 2);
+    }
+
+    fn config_using(id: &str, sources: &[&str]) -> DynamicPlaylistConfig {
+        let mut config = config_with(
+            sources
+                .iter()
+                .map(|s| SourceEntry::from(Source::DynamicConfig { id: s.to_string() }))
+                .collect(),
+            None,
+        );
+        config.id = id.to_string();
+        config
+    }
+
+    #[test]
+    fn dependencies_come_before_the_configs_using_them() {
+        let configs = vec![
+            config_using("c", &["b"]),
+            config_using("b", &["a", "missing"]),
+            config_using("a", &[]),
+        ];
+        let (ordered, blocked) = order_by_dependencies(&configs);
+        let ids: Vec<&str> = ordered.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert!(blocked.is_empty());
+    }
+
+    #[test]
+    fn configs_in_or_behind_a_cycle_are_blocked() {
+        let configs = vec![
+            config_using("a", &[]),
+            config_using("b", &["c"]),
+            config_using("c", &["b"]),
+            config_using("d", &["c"]),
+        ];
+        let (ordered, blocked) = order_by_dependencies(&configs);
+        assert_eq!(ordered.len(), 1);
+        let mut ids: Vec<&str> = blocked.iter().map(|c| c.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn cycle_error_names_the_loop_only() {
+        let chain = vec![
+            "test-x".to_string(),
+            "test-y".to_string(),
+            "test-z".to_string(),
+        ];
+        assert_eq!(
+            cycle_error(&chain, "test-y"),
+            "Dynamic playlists feed into each other: test-y → test-z → test-y"
+        );
+    }
+
+    fn track(uri: &str, artist: &str, album: &str, duration_ms: u32) -> TrackInfo {
+        TrackInfo {
+            uri: uri.to_string(),
+            id: uri.to_string(),
+            name: uri.to_string(),
+            artist: artist.to_string(),
+            album: album.to_string(),
+            album_type: "album".to_string(),
+            release_date: "2020-01-01".to_string(),
+            duration_ms,
+            explicit: false,
+            popularity: None,
+        }
+    }
+
+    fn uris(tracks: &[TrackInfo]) -> Vec<&str> {
+        tracks.iter().map(|t| t.uri.as_str()).collect()
+    }
+
+    #[test]
+    fn filter_rules_combine_with_all_or_any() {
+        let tracks = vec![
+            track("short-a", "A", "X", 1000),
+            track("long-a", "A", "X", 5000),
+            track("long-b", "B", "X", 5000),
+        ];
+        let mut filters = FilterConfig {
+            rules: vec![
+                FilterRule::Duration {
+                    min_ms: Some(2000),
+                    max_ms: None,
+                },
+                FilterRule::ArtistAllow {
+                    artists: vec!["a".to_string()],
+                },
+            ],
+            ..FilterConfig::default()
+        };
+        let all = apply_filters(tracks.clone(), &filters, None).unwrap();
+        assert_eq!(uris(&all), vec!["long-a"]);
+
+        filters.rule_match = RuleMatch::Any;
+        let any = apply_filters(tracks, &filters, None).unwrap();
+        assert_eq!(uris(&any), vec!["short-a", "long-a", "long-b"]);
+    }
+
+    #[test]
+    fn filter_rules_reject_invalid_patterns() {
+        let filters = FilterConfig {
+            rules: vec![FilterRule::Regex {
+                field: TrackField::Name,
+                pattern: "(".to_string(),
+                exclude: false,
+            }],
+            ..FilterConfig::default()
+        };
+        assert!(validate_filters(&filters).is_err());
+    }
+
+    #[test]
+    fn selection_caps_use_the_first_credited_artist() {
+        let tracks = vec![
+            track("1", "A, B", "X", 1000),
+            track("2", "A", "Y", 1000),
+            track("3", "B, A", "X", 1000),
+            track("4", "A", "Y", 1000),
+        ];
+        let selection = Selection {
+            max_per_artist: Some(2),
+            ..Selection::default()
+        };
+        assert_eq!(
+            uris(&select_tracks(tracks.clone(), &selection)),
+            vec!["1", "2", "3"]
+        );
+
+        let selection = Selection {
+            max_per_album: Some(1),
+            ..Selection::default()
+        };
+        assert_eq!(
+            uris(&select_tracks(tracks, &selection)),
+            vec!["1", "2", "3"]
+        );
+    }
+
+    #[test]
+    fn selection_orders_before_limiting() {
+        let tracks = vec![
+            track("short", "A", "X", 1000),
+            track("long", "B", "Y", 3000),
+            track("middle", "C", "Z", 2000),
+        ];
+        let selection = Selection {
+            order_by: vec![crate::logic::SortRule {
+                id: "0".to_string(),
+                criteria: "Duration".to_string(),
+                descending: true,
+            }],
+            limit: Some(2),
+            ..Selection::default()
+        };
+        assert_eq!(
+            uris(&select_tracks(tracks, &selection)),
+            vec!["long", "middle"]
+        );
+    }
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn rolling_puts_new_tracks_on_top_and_drops_the_oldest() {
+        let existing = vec![track("old", "A", "X", 1000), track("older", "A", "X", 1000)];
+        let added_at = HashMap::from([("old".to_string(), 100), ("older".to_string(), 50)]);
+        let window = RollingWindow {
+            max_tracks: Some(2),
+            ..RollingWindow::default()
+        };
+        let (uris, kept) = roll_tracks(
+            existing,
+            vec![track("new", "A", "X", 1000)],
+            &added_at,
+            &window,
+            200,
+        );
+        assert_eq!(uris, vec!["new", "old"]);
+        assert_eq!(kept.get("new"), Some(&200));
+        assert_eq!(kept.get("old"), Some(&100));
+    }
+
+    #[test]
+    fn rolling_drops_tracks_older_than_the_window() {
+        let now = 10 * DAY;
+        let existing = vec![
+            track("recent", "A", "X", 1000),
+            track("stale", "A", "X", 1000),
+        ];
+        let added_at = HashMap::from([
+            ("recent".to_string(), now - DAY),
+            ("stale".to_string(), now - 8 * DAY),
+        ]);
+        let window = RollingWindow {
+            max_age_days: Some(7),
+            ..RollingWindow::default()
+        };
+        let (uris, _) = roll_tracks(existing, Vec::new(), &added_at, &window, now);
+        assert_eq!(uris, vec!["recent"]);
+    }
+
+    #[test]
+    fn rolling_keeps_the_playlist_within_its_hours() {
+        let hour = 60 * 60 * 1000;
+        let existing = vec![track("b", "A", "X", hour), track("c", "A", "X", hour)];
+        let added_at = HashMap::from([("b".to_string(), 100), ("c".to_string(), 50)]);
+        let window = RollingWindow {
+            max_hours: Some(2),
+            ..RollingWindow::default()
+        };
+        let (uris, _) = roll_tracks(
+            existing,
+            vec![track("a", "A", "X", hour)],
+            &added_at,
+            &window,
+            200,
+        );
+        assert_eq!(uris, vec!["a", "b"]);
+    }
 }
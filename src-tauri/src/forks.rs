@@ -0,0 +1,263 @@
+use crate::spotify::{Playlist, SpotifyClient};
+use crate::storage::ListFile;
+use rspotify::model::PlaylistId;
+use rspotify::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// An editable copy of someone else's playlist, linked to the original
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkLink {
+    pub fork_id: String,
+    pub upstream_id: String,
+    pub upstream_name: String,
+    pub upstream_owner: String,
+    pub upstream_snapshot_id: String,
+    /// Upstream tracks at the last sync. Tells tracks removed upstream apart
+    /// from tracks we added to the fork ourselves.
+    pub upstream_uris: Vec<String>,
+    /// Local files of the original. The Web API can't add them, so they're
+    /// kept here for the user to add in the Spotify app.
+    #[serde(default)]
+    pub local_placeholders: Vec<LocalPlaceholder>,
+    pub created_at: String,
+    pub last_synced: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalPlaceholder {
+    pub uri: String,
+    pub name: String,
+    pub artist: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkSyncSummary {
+    pub added: usize,
+    pub removed: usize,
+}
+
+fn get_forks_path() -> PathBuf {
+    crate::profiles::current_profile_dir().join("forks.json")
+}
+
+const FORKS_FILE: ListFile = ListFile {
+    label: "fork links",
+    migrations: &[crate::storage::unchanged],
+};
+
+/// Links that fail to load are reported through `crate::storage::load_issues`
+pub fn load_forks() -> Vec<ForkLink> {
+    FORKS_FILE.load(&get_forks_path())
+}
+
+pub fn save_forks(forks: &[ForkLink]) -> Result<(), String> {
+    FORKS_FILE.save(&get_forks_path(), forks)
+}
+
+pub fn find_fork(fork_id: &str) -> Option<ForkLink> {
+    load_forks().into_iter().find(|f| f.fork_id == fork_id)
+}
+
+fn store_fork(link: ForkLink) -> Result<(), String> {
+    let mut forks = load_forks();
+    forks.retain(|f| f.fork_id != link.fork_id);
+    forks.push(link);
+    save_forks(&forks)
+}
+
+fn split_local(tracks: &[crate::logic::AppTrack]) -> (Vec<String>, Vec<LocalPlaceholder>) {
+    let mut uris = Vec::new();
+    let mut local = Vec::new();
+    for track in tracks {
+        if track.uri.starts_with("spotify:local") {
+            local.push(LocalPlaceholder {
+                uri: track.uri.clone(),
+                name: track.name.clone(),
+                artist: track.artist_names.clone(),
+            });
+        } else {
+            uris.push(track.uri.clone());
+        }
+    }
+    (uris, local)
+}
+
+/// Copy a playlist into a new private playlist owned by the user
+pub async fn fork_playlist(
+    client: &SpotifyClient,
+    user_id: &str,
+    upstream_id: &str,
+    name: Option<String>,
+    app_handle: &tauri::AppHandle,
+) -> Result<(Playlist, ForkLink), String> {
+    let pid = PlaylistId::from_id(upstream_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let upstream = client
+        .playlist(pid, None, None)
+        .await
+        .map_err(|e| format!("Failed to get playlist: {}", e))?;
+
+    let (_, tracks) =
+        crate::spotify::fetch_playlist_tracks(client, upstream_id, app_handle).await?;
+    let (uris, local_placeholders) = split_local(&tracks);
+
+    let owner = upstream
+        .owner
+        .display_name
+        .clone()
+        .unwrap_or_else(|| upstream.owner.id.id().to_string());
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .unwrap_or_else(|| format!("{} (Fork)", upstream.name));
+    let description = format!("Fork of {} by {}", upstream.name, owner);
+
+    let playlist = crate::spotify::create_playlist(client, user_id, &name, &description).await?;
+    if let Err(e) =
+        crate::spotify::update_playlist_items(client, &playlist.id, uris.clone(), None, app_handle)
+            .await
+    {
+        // Don't leave an empty copy behind
+        if let Err(delete_error) = crate::spotify::delete_playlist(client, &playlist.id).await {
+            println!("Couldn't delete fork {}: {}", playlist.id, delete_error);
+        }
+        return Err(e);
+    }
+
+    let now = chrono::Local::now().to_rfc3339();
+    let link = ForkLink {
+        fork_id: playlist.id.clone(),
+        upstream_id: upstream_id.to_string(),
+        upstream_name: upstream.name,
+        upstream_owner: owner,
+        upstream_snapshot_id: upstream.snapshot_id,
+        upstream_uris: uris,
+        local_placeholders,
+        created_at: now.clone(),
+        last_synced: now,
+    };
+    store_fork(link.clone())?;
+
+    Ok((playlist, link))
+}
+
+/// Upstream changes of a fork, worked out without writing anything
+pub struct ForkSync {
+    /// The link as it is once the changes are in the fork
+    pub link: ForkLink,
+    removed: HashSet<String>,
+    added: Vec<crate::logic::AppTrack>,
+}
+
+impl ForkSync {
+    /// Pull the changes into the fork's tracks: tracks added upstream are
+    /// appended, tracks removed upstream are dropped, and our own additions stay.
+    pub fn apply(&self, tracks: &mut Vec<crate::logic::AppTrack>) -> ForkSyncSummary {
+        let before = tracks.len();
+        tracks.retain(|t| !self.removed.contains(&t.uri));
+        let removed = before - tracks.len();
+
+        let in_fork: HashSet<String> = tracks.iter().map(|t| t.uri.clone()).collect();
+        let added: Vec<_> = self
+            .added
+            .iter()
+            .filter(|t| !in_fork.contains(&t.uri))
+            .cloned()
+            .collect();
+        let summary = ForkSyncSummary {
+            added: added.len(),
+            removed,
+        };
+        tracks.extend(added);
+        summary
+    }
+}
+
+/// Check the original of a fork for changes since the last sync. None when
+/// it hasn't changed.
+pub async fn check_fork(
+    client: &SpotifyClient,
+    link: &ForkLink,
+    app_handle: &tauri::AppHandle,
+) -> Result<Option<ForkSync>, String> {
+    let pid = PlaylistId::from_id(&link.upstream_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let upstream = client
+        .playlist(pid, None, None)
+        .await
+        .map_err(|e| format!("Failed to get upstream playlist: {}", e))?;
+
+    if upstream.snapshot_id == link.upstream_snapshot_id {
+        return Ok(None);
+    }
+
+    let (_, upstream_tracks) =
+        crate::spotify::fetch_playlist_tracks(client, &link.upstream_id, app_handle).await?;
+    let (upstream_uris, local_placeholders) = split_local(&upstream_tracks);
+
+    let known: HashSet<&String> = link.upstream_uris.iter().collect();
+    let current: HashSet<&String> = upstream_uris.iter().collect();
+    let removed = known
+        .difference(&current)
+        .map(|uri| uri.to_string())
+        .collect();
+    let added = upstream_tracks
+        .into_iter()
+        .filter(|t| !t.uri.starts_with("spotify:local") && !known.contains(&t.uri))
+        .collect();
+
+    let link = ForkLink {
+        upstream_name: upstream.name,
+        upstream_snapshot_id: upstream.snapshot_id,
+        upstream_uris,
+        local_placeholders,
+        last_synced: chrono::Local::now().to_rfc3339(),
+        ..link.clone()
+    };
+    Ok(Some(ForkSync {
+        link,
+        removed,
+        added,
+    }))
+}
+
+/// Remember that a fork has the changes of a sync
+pub fn finish_sync(sync: ForkSync) -> Result<(), String> {
+    store_fork(sync.link)
+}
+
+/// Pull upstream changes into a fork and write it to Spotify right away
+pub async fn sync_fork(
+    client: &SpotifyClient,
+    link: &ForkLink,
+    app_handle: &tauri::AppHandle,
+) -> Result<ForkSyncSummary, String> {
+    let Some(sync) = check_fork(client, link, app_handle).await? else {
+        return Ok(ForkSyncSummary {
+            added: 0,
+            removed: 0,
+        });
+    };
+
+    let (_, mut tracks) =
+        crate::spotify::fetch_playlist_tracks(client, &link.fork_id, app_handle).await?;
+    let fork_uris: Vec<String> = tracks.iter().map(|t| t.uri.clone()).collect();
+    let summary = sync.apply(&mut tracks);
+
+    if summary.removed > 0 || summary.added > 0 {
+        crate::spotify::update_playlist_items(
+            client,
+            &link.fork_id,
+            tracks.into_iter().map(|t| t.uri).collect(),
+            Some(fork_uris),
+            app_handle,
+        )
+        .await?;
+        crate::spotify::invalidate_playlist_cache(&link.fork_id);
+    }
+    finish_sync(sync)?;
+
+    Ok(summary)
+}
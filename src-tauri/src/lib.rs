@@ -1,21 +1,31 @@
 #[macro_use]
 pub mod debug_log;
+pub mod bans;
+pub mod bundles;
 pub mod commands;
 pub mod dynamic;
+pub mod forks;
 pub mod logic;
+pub mod profiles;
 pub mod scheduler;
 pub mod spotify;
+pub mod storage;
 pub mod tray;
+pub mod vault;
+pub mod versions;
 
 use spotify::SpotifyState;
+use std::collections::HashMap;
 use std::sync::Mutex;
 use tauri::Manager;
 // use tauri_plugin_store::StoreBuilder;
 use tauri_plugin_store::StoreExt;
 
 pub struct AppState {
-    pub spotify: Mutex<SpotifyState>,
+    pub spotify: Mutex<SpotifyState>,                   // Active profile
+    pub sessions: Mutex<HashMap<String, SpotifyState>>, // Other profiles, by profile ID
     pub history_lock: Mutex<()>,
+    pub auth_cancel: Mutex<Option<tokio::sync::oneshot::Sender<()>>>, // Pending OAuth login
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -31,7 +41,9 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(AppState {
             spotify: Mutex::new(SpotifyState::default()),
+            sessions: Mutex::new(HashMap::new()),
             history_lock: Mutex::new(()),
+            auth_cancel: Mutex::new(None),
         })
         .on_window_event(|window, event| {
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
@@ -79,7 +91,11 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             commands::check_auth,
             commands::initialize_spotify,
+            commands::cancel_auth,
             commands::logout,
+            commands::unlock_credentials,
+            commands::rotate_credentials_key,
+            commands::wipe_secrets,
             commands::scan_playlist,
             commands::apply_changes,
             commands::open_url,
@@ -93,6 +109,9 @@ pub fn run() {
             commands::delete_history_item,
             commands::clear_history,
             commands::get_ignored_tracks,
+            commands::get_load_issues,
+            commands::get_ban_list,
+            commands::save_ban_list,
             commands::get_backups,
             commands::restore_from_file,
             commands::get_dynamic_configs,
@@ -100,13 +119,30 @@ pub fn run() {
             commands::delete_dynamic_config,
             commands::run_dynamic_update,
             commands::run_all_dynamic_updates,
+            commands::preview_dynamic_update,
+            commands::export_config_bundle,
+            commands::import_config_bundle,
             commands::compare_playlists,
             commands::remove_track_from_playlist,
             commands::export_m3u,
+            commands::update_playlist_details,
+            commands::upload_playlist_cover,
+            commands::open_playlist,
+            commands::refresh_playlists,
+            commands::delete_playlist,
+            commands::fork_playlist,
+            commands::get_forks,
+            commands::sync_fork,
+            commands::unlink_fork,
             // Desktop Schedule commands
             commands::get_desktop_schedules,
             commands::save_desktop_schedule,
             commands::delete_desktop_schedule,
+            // Profile commands
+            commands::get_profiles,
+            commands::add_profile,
+            commands::switch_profile,
+            commands::remove_profile,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
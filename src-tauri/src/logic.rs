@@ -12,6 +12,8 @@ pub struct AppTrack {
     pub release_date: String,
     pub uri: String,
     pub duration_ms: u32,
+    #[serde(default)]
+    pub popularity: Option<u32>,
 }
 
 // Helper for decoding URI components
@@ -83,6 +85,10 @@ impl AppTrack {
             .get("duration_ms")
             .and_then(|t| t.as_u64())
             .unwrap_or(0) as u32;
+        let popularity = track_val
+            .get("popularity")
+            .and_then(|t| t.as_u64())
+            .map(|p| p as u32);
 
         let mut artists =
             if let Some(artist_list) = track_val.get("artists").and_then(|a| a.as_array()) {
@@ -145,6 +151,7 @@ impl AppTrack {
             release_date,
             uri,
             duration_ms,
+            popularity,
         })
     }
 
@@ -199,6 +206,7 @@ impl AppTrack {
                 release_date: item.album.release_date.clone().unwrap_or_default(),
                 uri: uri.clone(),
                 duration_ms: item.duration.num_milliseconds() as u32,
+                popularity: Some(item.popularity),
             })
             .unwrap()
             .as_object()
@@ -276,6 +284,7 @@ pub fn sort_tracks(mut tracks: Vec<AppTrack>, rules: &[SortRule]) -> Vec<AppTrac
                     date_a.cmp(&date_b)
                 }
                 "Duration" => a.duration_ms.cmp(&b.duration_ms),
+                "Popularity" => a.popularity.cmp(&b.popularity),
                 _ => std::cmp::Ordering::Equal,
             };
 
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+tokio::task_local! {
+    /// Profile a background task works on, overriding the active one
+    static SCOPED_PROFILE: String;
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesFile {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfilesFile {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+                created_at: chrono::Local::now().to_rfc3339(),
+            }],
+        }
+    }
+}
+
+/// App data root, shared by all profiles
+pub fn root_data_dir() -> PathBuf {
+    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("Spotify Sorter");
+    fs::create_dir_all(&path).ok();
+    path
+}
+
+/// Data dir of a profile. The default profile uses the root so data from
+/// before profiles existed stays where it is.
+pub fn profile_dir(profile_id: &str) -> PathBuf {
+    let mut path = root_data_dir();
+    if profile_id != DEFAULT_PROFILE_ID {
+        path.push("profiles");
+        path.push(profile_id);
+        fs::create_dir_all(&path).ok();
+    }
+    path
+}
+
+/// Data dir of the profile the current task works on
+pub fn current_profile_dir() -> PathBuf {
+    profile_dir(&current_profile_id())
+}
+
+/// Profile of the current task: the scoped one inside `with_profile`, the active one otherwise
+pub fn current_profile_id() -> String {
+    SCOPED_PROFILE
+        .try_with(|id| id.clone())
+        .unwrap_or_else(|_| load_profiles().active)
+}
+
+/// Run `f` with all data paths pointing at `profile_id`
+pub async fn with_profile<F: Future>(profile_id: String, f: F) -> F::Output {
+    SCOPED_PROFILE.scope(profile_id, f).await
+}
+
+/// Like `with_profile`, for synchronous code
+pub fn sync_with_profile<R>(profile_id: &str, f: impl FnOnce() -> R) -> R {
+    SCOPED_PROFILE.sync_scope(profile_id.to_string(), f)
+}
+
+fn get_profiles_path() -> PathBuf {
+    root_data_dir().join("profiles.json")
+}
+
+pub fn load_profiles() -> ProfilesFile {
+    let path = get_profiles_path();
+    if let Ok(content) = fs::read_to_string(path) {
+        if let Ok(file) = serde_json::from_str::<ProfilesFile>(&content) {
+            if file.profiles.iter().any(|p| p.id == file.active) {
+                return file;
+            }
+        }
+    }
+    ProfilesFile::default()
+}
+
+pub fn save_profiles(file: &ProfilesFile) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+    fs::write(get_profiles_path(), json).map_err(|e| format!("Failed to save profiles: {}", e))
+}
+
+pub fn add_profile(name: &str) -> Result<Profile, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    let mut file = load_profiles();
+    if file
+        .profiles
+        .iter()
+        .any(|p| p.name.eq_ignore_ascii_case(name))
+    {
+        return Err(format!("A profile named '{}' already exists", name));
+    }
+
+    let profile = Profile {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    profile_dir(&profile.id);
+
+    file.profiles.push(profile.clone());
+    save_profiles(&file)?;
+    Ok(profile)
+}
+
+pub fn set_active_profile(profile_id: &str) -> Result<(), String> {
+    let mut file = load_profiles();
+    if !file.profiles.iter().any(|p| p.id == profile_id) {
+        return Err("Profile not found".to_string());
+    }
+    file.active = profile_id.to_string();
+    save_profiles(&file)
+}
+
+/// Remove a profile and all of its data. The default and active profiles can't be removed.
+pub fn remove_profile(profile_id: &str) -> Result<(), String> {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return Err("The default profile can't be removed".to_string());
+    }
+
+    let mut file = load_profiles();
+    if file.active == profile_id {
+        return Err("Switch to another profile before removing this one".to_string());
+    }
+
+    let initial_len = file.profiles.len();
+    file.profiles.retain(|p| p.id != profile_id);
+    if file.profiles.len() == initial_len {
+        return Err("Profile not found".to_string());
+    }
+    save_profiles(&file)?;
+
+    let dir = profile_dir(profile_id);
+    fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete profile data: {}", e))
+}
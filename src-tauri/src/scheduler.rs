@@ -1,8 +1,8 @@
+use crate::storage::ListFile;
 use crate::AppState;
 use chrono::Local;
 use cron::Schedule as CronSchedule;
 use serde::{Deserialize, Serialize};
-use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::thread;
@@ -18,29 +18,32 @@ pub struct DesktopSchedule {
     pub last_run: Option<String>,
 }
 
-fn get_schedules_path() -> PathBuf {
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("Spotify Sorter");
-    path.push("schedules.json");
-    path
+fn get_schedules_path(profile_id: &str) -> PathBuf {
+    crate::profiles::profile_dir(profile_id).join("schedules.json")
 }
 
+/// Load the schedules of the current profile
 pub fn load_schedules() -> Vec<DesktopSchedule> {
-    let path = get_schedules_path();
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(schedules) = serde_json::from_str(&content) {
-                return schedules;
-            }
-        }
-    }
-    Vec::new()
+    load_profile_schedules(&crate::profiles::current_profile_id())
+}
+
+/// Save the schedules of the current profile
+pub fn save_schedules(schedules: &[DesktopSchedule]) {
+    save_profile_schedules(&crate::profiles::current_profile_id(), schedules)
+}
+
+const SCHEDULES_FILE: ListFile = ListFile {
+    label: "schedules",
+    migrations: &[crate::storage::unchanged],
+};
+
+fn load_profile_schedules(profile_id: &str) -> Vec<DesktopSchedule> {
+    SCHEDULES_FILE.load(&get_schedules_path(profile_id))
 }
 
-pub fn save_schedules(schedules: &Vec<DesktopSchedule>) {
-    let path = get_schedules_path();
-    if let Ok(json) = serde_json::to_string_pretty(schedules) {
-        let _ = fs::write(path, json);
+fn save_profile_schedules(profile_id: &str, schedules: &[DesktopSchedule]) {
+    if let Err(e) = SCHEDULES_FILE.save(&get_schedules_path(profile_id), schedules) {
+        println!("{}", e);
     }
 }
 
@@ -51,58 +54,73 @@ pub fn start_scheduler_loop(app: AppHandle) {
             // Check every minute
             thread::sleep(Duration::from_secs(60));
 
-            let schedules = load_schedules();
             let now = Local::now();
 
-            for schedule in schedules {
-                if !schedule.enabled {
-                    continue;
-                }
+            // Every profile's jobs run, not just the active one's
+            for profile in crate::profiles::load_profiles().profiles {
+                run_due_schedules(&app, &profile.id, now);
+            }
+        }
+    });
+}
+
+fn run_due_schedules(app: &AppHandle, profile_id: &str, now: chrono::DateTime<Local>) {
+    let schedules = load_profile_schedules(profile_id);
+
+    for schedule in schedules {
+        if !schedule.enabled {
+            continue;
+        }
+
+        if let Ok(cron) = CronSchedule::from_str(&schedule.cron_expression) {
+            // Check if the schedule should run in the current minute window
+
+            let check_base = now - chrono::Duration::seconds(61);
+            if let Some(next_run) = cron.after(&check_base).next() {
+                let next_run_local = next_run.with_timezone(&Local);
 
-                if let Ok(cron) = CronSchedule::from_str(&schedule.cron_expression) {
-                    // Check if the schedule should run in the current minute window
+                // If the schedule matches the current time window
+                if next_run_local <= now && next_run_local > check_base {
+                    println!(
+                        "Running schedule for config: {} (profile {})",
+                        schedule.config_id, profile_id
+                    );
 
-                    let check_base = now - chrono::Duration::seconds(61);
-                    if let Some(next_run) = cron.after(&check_base).next() {
-                        let next_run_local = next_run.with_timezone(&Local);
+                    // EXECUTE
+                    let app_handle = app.clone();
+                    let config_id = schedule.config_id.clone();
+                    let schedule_id = schedule.id.clone();
+                    let profile_id = profile_id.to_string();
 
-                        // If the schedule matches the current time window
-                        if next_run_local <= now && next_run_local > check_base {
-                            println!("Running schedule for config: {}", schedule.config_id);
+                    // Update last_run immediately
+                    let mut all_schedules = load_profile_schedules(&profile_id);
+                    if let Some(s) = all_schedules.iter_mut().find(|x| x.id == schedule_id) {
+                        s.last_run = Some(now.to_rfc3339());
+                    }
+                    save_profile_schedules(&profile_id, &all_schedules);
 
-                            // EXECUTE
-                            let app_handle = app.clone();
-                            let config_id = schedule.config_id.clone();
-                            let schedule_id = schedule.id.clone();
+                    tauri::async_runtime::spawn(crate::profiles::with_profile(
+                        profile_id.clone(),
+                        async move {
+                            let state = app_handle.state::<AppState>();
 
-                            // Update last_run immediately
-                            let mut all_schedules = load_schedules();
-                            if let Some(s) = all_schedules.iter_mut().find(|x| x.id == schedule_id)
+                            match crate::commands::run_profile_dynamic_update(
+                                &app_handle,
+                                &state,
+                                &profile_id,
+                                &config_id,
+                            )
+                            .await
                             {
-                                s.last_run = Some(now.to_rfc3339());
-                            }
-                            save_schedules(&all_schedules);
-
-                            tauri::async_runtime::spawn(async move {
-                                let state = app_handle.state::<AppState>();
-
-                                match crate::commands::run_dynamic_playlist_logic(
-                                    &app_handle,
-                                    &state,
-                                    &config_id,
-                                )
-                                .await
-                                {
-                                    Ok(_) => println!("Scheduled run success: {}", config_id),
-                                    Err(e) => {
-                                        println!("Scheduled run failed: {} - {}", config_id, e)
-                                    }
+                                Ok(_) => println!("Scheduled run success: {}", config_id),
+                                Err(e) => {
+                                    println!("Scheduled run failed: {} - {}", config_id, e)
                                 }
-                            });
-                        }
-                    }
+                            }
+                        },
+                    ));
                 }
             }
         }
-    });
+    }
 }
@@ -1,11 +1,38 @@
 use rspotify::{
-    model::SimplifiedPlaylist, prelude::*, scopes, AuthCodeSpotify, Credentials, OAuth,
+    http::HttpClient,
+    model::{FullPlaylist, PublicUser, SimplifiedPlaylist},
+    prelude::*,
+    AuthCodePkceSpotify, AuthCodeSpotify, ClientResult, Config, Credentials, OAuth, Token,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use tauri::Emitter;
 
-pub const REDIRECT_URI: &str = "http://127.0.0.1:27196";
+pub const DEFAULT_CALLBACK_PORT: u16 = 27196;
+
+const PLAYLIST_SCOPES: &[&str] = &[
+    "playlist-read-private",
+    "playlist-read-collaborative",
+    "playlist-modify-public",
+    "playlist-modify-private",
+    "user-library-read",
+];
+
+/// Only needed by the top tracks and recently played sources, so sessions
+/// from before they existed can still edit playlists
+const LISTENING_SCOPES: &[&str] = &["user-top-read", "user-read-recently-played"];
+
+/// Only needed to upload playlist covers
+const COVER_SCOPES: &[&str] = &["ugc-image-upload"];
+
+/// How long to wait for the user to finish authorizing in the browser
+const CALLBACK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5 * 60);
+
+/// Redirect URI for a callback port. Has to match the one registered in the
+/// Spotify Developer Dashboard exactly.
+pub fn redirect_uri(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Playlist {
@@ -16,6 +43,13 @@ pub struct Playlist {
     pub collaborative: bool,
     #[serde(rename = "isPublic")]
     pub is_public: bool,
+    #[serde(rename = "ownerName", default)]
+    pub owner_name: String,
+    #[serde(rename = "trackCount", default)]
+    pub track_count: u32,
+    /// Changes whenever the playlist's tracks or details change
+    #[serde(rename = "snapshotId", default)]
+    pub snapshot_id: String,
 }
 
 impl Playlist {
@@ -30,6 +64,281 @@ impl Playlist {
             editable: can_edit,
             collaborative: item.collaborative,
             is_public: item.public.unwrap_or(false),
+            owner_name: owner_display_name(&item.owner),
+            track_count: item.tracks.total,
+            snapshot_id: item.snapshot_id.clone(),
+        }
+    }
+
+    pub fn from_full(item: &FullPlaylist, user_id: &str) -> Self {
+        let owner_id = item.owner.id.to_string();
+        let can_edit = owner_id == user_id || item.collaborative;
+
+        Playlist {
+            id: item.id.id().to_string(),
+            name: item.name.clone(),
+            owner: owner_id,
+            editable: can_edit,
+            collaborative: item.collaborative,
+            is_public: item.public.unwrap_or(false),
+            owner_name: owner_display_name(&item.owner),
+            track_count: item.tracks.total,
+            snapshot_id: item.snapshot_id.clone(),
+        }
+    }
+}
+
+fn owner_display_name(owner: &PublicUser) -> String {
+    owner
+        .display_name
+        .clone()
+        .unwrap_or_else(|| owner.id.id().to_string())
+}
+
+/// Extract a playlist ID from a bare ID, a `spotify:playlist:` URI or an
+/// open.spotify.com URL
+pub fn parse_playlist_id(input: &str) -> Result<String, String> {
+    parse_spotify_id(input, "playlist")
+}
+
+/// Extract an artist ID from a bare ID, a `spotify:artist:` URI or an
+/// open.spotify.com URL
+pub fn parse_artist_id(input: &str) -> Result<String, String> {
+    parse_spotify_id(input, "artist")
+}
+
+/// Extract a track ID from a bare ID, a `spotify:track:` URI or an
+/// open.spotify.com URL
+pub fn parse_track_id(input: &str) -> Result<String, String> {
+    parse_spotify_id(input, "track")
+}
+
+fn parse_spotify_id(input: &str, kind: &str) -> Result<String, String> {
+    let input = input.trim();
+
+    let id = if let Some(pos) = input.find("open.spotify.com/") {
+        // https://open.spotify.com/(intl-de/)(user/NAME/)playlist/ID?si=...
+        let path = &input[pos + "open.spotify.com/".len()..];
+        let path = path.split(['?', '#']).next().unwrap_or("");
+        let mut segments = path.split('/');
+        segments.by_ref().find(|s| *s == kind);
+        segments.next().unwrap_or("")
+    } else if input.starts_with("spotify:") {
+        // spotify:playlist:ID or the old spotify:user:NAME:playlist:ID
+        input
+            .rsplit_once(&format!(":{}:", kind))
+            .map(|(_, id)| id)
+            .unwrap_or("")
+    } else {
+        input
+    };
+
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(id.to_string())
+    } else {
+        Err(format!("Not a Spotify {}: {}", kind, input))
+    }
+}
+
+/// Metadata of any playlist, including ones outside the user's library
+pub async fn fetch_playlist_info(
+    client: &SpotifyClient,
+    playlist_id: &str,
+    user_id: &str,
+) -> Result<Playlist, String> {
+    use rspotify::model::PlaylistId;
+
+    let pid = PlaylistId::from_id(playlist_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let full = client
+        .playlist(pid, None, None)
+        .await
+        .map_err(|e| format!("Failed to get playlist {}: {}", playlist_id, e))?;
+    Ok(Playlist::from_full(&full, user_id))
+}
+
+/// Where processed tracks are written
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OutputTarget {
+    #[default]
+    InPlace, // Overwrite the source playlist
+    NewPlaylist {
+        #[serde(default)]
+        name: Option<String>, // Generated from the source name if missing
+    },
+    ExistingPlaylist {
+        id: String,
+    },
+}
+
+/// Which OAuth flow a session was authorized with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthFlow {
+    AuthCode, // Client ID + secret
+    Pkce,     // Client ID only
+}
+
+/// Called with every token a client receives, including automatic refreshes
+pub type TokenHook = std::sync::Arc<dyn Fn(&Token) + Send + Sync>;
+
+/// Spotify client for either auth flow. Both variants expose the same Web API,
+/// so everything outside of auth only deals with this type.
+#[derive(Clone)]
+pub struct SpotifyClient {
+    api: SpotifyApi,
+    on_token: Option<TokenHook>,
+}
+
+#[derive(Debug, Clone)]
+enum SpotifyApi {
+    AuthCode(AuthCodeSpotify),
+    Pkce(AuthCodePkceSpotify),
+}
+
+impl std::fmt::Debug for SpotifyClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpotifyClient")
+            .field("api", &self.api)
+            .finish()
+    }
+}
+
+impl SpotifyClient {
+    /// Build an unauthorized client. A missing or blank secret selects PKCE.
+    pub fn new(client_id: &str, client_secret: Option<&str>, callback_port: u16) -> Self {
+        let oauth = OAuth {
+            redirect_uri: redirect_uri(callback_port),
+            scopes: SpotifyState::get_scopes(),
+            ..Default::default()
+        };
+
+        let api = match client_secret.filter(|s| !s.trim().is_empty()) {
+            Some(secret) => SpotifyApi::AuthCode(AuthCodeSpotify::new(
+                Credentials::new(client_id, secret),
+                oauth,
+            )),
+            None => SpotifyApi::Pkce(AuthCodePkceSpotify::new(
+                Credentials::new_pkce(client_id),
+                oauth,
+            )),
+        };
+        SpotifyClient {
+            api,
+            on_token: None,
+        }
+    }
+
+    /// Get notified of every new token, e.g. to persist refreshed tokens
+    pub fn set_token_hook(&mut self, hook: TokenHook) {
+        self.on_token = Some(hook);
+    }
+
+    /// Scopes the app needs but the current token wasn't granted
+    pub async fn missing_scopes(&self) -> Vec<String> {
+        self.missing_scopes_of(&SpotifyState::get_scopes()).await
+    }
+
+    /// Which of `needed` the current token wasn't granted
+    pub async fn missing_scopes_of(&self, needed: &HashSet<String>) -> Vec<String> {
+        let granted = match self.get_token().lock().await.unwrap().as_ref() {
+            // Tokens without a scope list don't tell us anything
+            Some(token) if !token.scopes.is_empty() => token.scopes.clone(),
+            _ => return Vec::new(),
+        };
+        let mut missing: Vec<String> = needed.difference(&granted).cloned().collect();
+        missing.sort();
+        missing
+    }
+
+    pub fn auth_flow(&self) -> AuthFlow {
+        match self.api {
+            SpotifyApi::AuthCode(_) => AuthFlow::AuthCode,
+            SpotifyApi::Pkce(_) => AuthFlow::Pkce,
+        }
+    }
+
+    /// Get the URL the user has to visit to authorize the app.
+    /// For PKCE this also generates the code verifier used by `request_token`.
+    pub fn get_authorize_url(&mut self) -> ClientResult<String> {
+        match &mut self.api {
+            SpotifyApi::AuthCode(c) => c.get_authorize_url(false),
+            SpotifyApi::Pkce(c) => c.get_authorize_url(None),
+        }
+    }
+}
+
+impl Default for SpotifyClient {
+    fn default() -> Self {
+        SpotifyClient {
+            api: SpotifyApi::AuthCode(AuthCodeSpotify::default()),
+            on_token: None,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl BaseClient for SpotifyClient {
+    fn get_config(&self) -> &Config {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.get_config(),
+            SpotifyApi::Pkce(c) => c.get_config(),
+        }
+    }
+
+    fn get_http(&self) -> &HttpClient {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.get_http(),
+            SpotifyApi::Pkce(c) => c.get_http(),
+        }
+    }
+
+    fn get_token(&self) -> std::sync::Arc<rspotify::sync::Mutex<Option<Token>>> {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.get_token(),
+            SpotifyApi::Pkce(c) => c.get_token(),
+        }
+    }
+
+    fn get_creds(&self) -> &Credentials {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.get_creds(),
+            SpotifyApi::Pkce(c) => c.get_creds(),
+        }
+    }
+
+    async fn refetch_token(&self) -> ClientResult<Option<Token>> {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.refetch_token().await,
+            SpotifyApi::Pkce(c) => c.refetch_token().await,
+        }
+    }
+
+    /// rspotify calls this after every token request and refresh. We keep no
+    /// plain-text token cache and hand the token to the hook instead.
+    async fn write_token_cache(&self) -> ClientResult<()> {
+        if let Some(hook) = &self.on_token {
+            if let Some(token) = self.get_token().lock().await.unwrap().as_ref() {
+                hook(token);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl OAuthClient for SpotifyClient {
+    fn get_oauth(&self) -> &OAuth {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.get_oauth(),
+            SpotifyApi::Pkce(c) => c.get_oauth(),
+        }
+    }
+
+    async fn request_token(&self, code: &str) -> ClientResult<()> {
+        match &self.api {
+            SpotifyApi::AuthCode(c) => c.request_token(code).await,
+            SpotifyApi::Pkce(c) => c.request_token(code).await,
         }
     }
 }
@@ -42,7 +351,8 @@ pub struct SpotifyState {
     pub refresh_token: Option<String>,
     pub user_id: Option<String>,
     pub playlists: Vec<Playlist>,
-    pub client: Option<AuthCodeSpotify>,
+    pub client: Option<SpotifyClient>,
+    pub credentials_key: Option<crate::vault::CredentialsKey>,
 }
 
 impl SpotifyState {
@@ -51,39 +361,62 @@ impl SpotifyState {
     }
 
     pub fn get_scopes() -> HashSet<String> {
-        scopes!(
-            "playlist-read-private",
-            "playlist-read-collaborative",
-            "playlist-modify-public",
-            "playlist-modify-private",
-            "user-library-read"
-        )
+        PLAYLIST_SCOPES
+            .iter()
+            .chain(LISTENING_SCOPES)
+            .chain(COVER_SCOPES)
+            .map(|scope| scope.to_string())
+            .collect()
+    }
+
+    /// Scopes playlist reads and edits need
+    pub fn playlist_scopes() -> HashSet<String> {
+        PLAYLIST_SCOPES
+            .iter()
+            .map(|scope| scope.to_string())
+            .collect()
+    }
+
+    /// Scopes uploading a playlist cover needs
+    pub fn cover_scopes() -> HashSet<String> {
+        PLAYLIST_SCOPES
+            .iter()
+            .chain(COVER_SCOPES)
+            .map(|scope| scope.to_string())
+            .collect()
     }
 }
 
 pub async fn do_spotify_auth(
     client_id: String,
-    client_secret: String,
-) -> Result<(AuthCodeSpotify, String, Vec<Playlist>), String> {
-    let creds = Credentials::new(&client_id, &client_secret);
-    let oauth = OAuth {
-        redirect_uri: REDIRECT_URI.to_string(),
-        scopes: SpotifyState::get_scopes(),
-        ..Default::default()
-    };
-
-    let spotify = AuthCodeSpotify::new(creds, oauth);
+    client_secret: Option<String>,
+    callback_port: u16,
+    cancel: tokio::sync::oneshot::Receiver<()>,
+) -> Result<(SpotifyClient, String, Vec<Playlist>), String> {
+    let mut spotify = SpotifyClient::new(&client_id, client_secret.as_deref(), callback_port);
 
     // Get the authorization URL
     let auth_url = spotify
-        .get_authorize_url(false)
+        .get_authorize_url()
         .map_err(|e| format!("Failed to get auth URL: {}", e))?;
 
+    // Bind before opening the browser so a busy port fails fast
+    let listener = bind_callback(callback_port)
+        .await
+        .map_err(|e| e.to_string())?;
+
     // Open browser for user to authorize
     open::that(&auth_url).map_err(|e| format!("Failed to open browser: {}", e))?;
 
-    // Start a simple HTTP server to receive the callback
-    let code = wait_for_callback().await?;
+    // Wait for the browser to be redirected back to us
+    let expected_state = spotify.get_oauth().state.clone();
+    let code = tokio::select! {
+        result = tokio::time::timeout(CALLBACK_TIMEOUT, wait_for_callback(&listener, &expected_state)) => {
+            result.unwrap_or(Err(CallbackError::Timeout))
+        }
+        _ = cancel => Err(CallbackError::Cancelled),
+    }
+    .map_err(|e| e.to_string())?;
 
     // Exchange code for token
     spotify
@@ -112,14 +445,11 @@ pub struct PlaylistsCacheEntry {
 }
 
 pub async fn fetch_all_playlists(
-    spotify: &AuthCodeSpotify,
+    spotify: &SpotifyClient,
     user_id: &str,
 ) -> Result<Vec<Playlist>, String> {
     use std::fs;
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-    path.push("Spotify Sorter");
-    fs::create_dir_all(&path).ok();
-    path.push("playlists_cache.json");
+    let path = crate::profiles::current_profile_dir().join("playlists_cache.json");
 
     // 1. Check Cache
     if path.exists() {
@@ -140,6 +470,79 @@ pub async fn fetch_all_playlists(
 
     println!("Playlist list Cache MISS. Fetching from API...");
 
+    let playlists = fetch_playlists_from_api(spotify, user_id).await?;
+    save_playlists_cache(user_id, &playlists);
+
+    Ok(playlists)
+}
+
+/// Which playlists appeared, disappeared or changed since the cached list
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistListChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+/// Refetch the playlist list regardless of the cache's age. Playlists whose
+/// snapshot changed get their cached tracks dropped.
+pub async fn refresh_all_playlists(
+    spotify: &SpotifyClient,
+    user_id: &str,
+) -> Result<(Vec<Playlist>, PlaylistListChanges), String> {
+    let previous = load_playlists_cache()
+        .filter(|cache| cache.user_id == user_id)
+        .map(|cache| cache.playlists)
+        .unwrap_or_default();
+
+    let playlists = fetch_playlists_from_api(spotify, user_id).await?;
+
+    let mut changes = PlaylistListChanges::default();
+    for playlist in &playlists {
+        match previous.iter().find(|p| p.id == playlist.id) {
+            None => changes.added.push(playlist.id.clone()),
+            Some(old) if old.snapshot_id != playlist.snapshot_id => {
+                invalidate_playlist_cache(&playlist.id);
+                changes.changed.push(playlist.id.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    for old in &previous {
+        if !playlists.iter().any(|p| p.id == old.id) {
+            invalidate_playlist_cache(&old.id);
+            changes.removed.push(old.id.clone());
+        }
+    }
+
+    save_playlists_cache(user_id, &playlists);
+    Ok((playlists, changes))
+}
+
+fn load_playlists_cache() -> Option<PlaylistsCacheEntry> {
+    let path = crate::profiles::current_profile_dir().join("playlists_cache.json");
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_playlists_cache(user_id: &str, playlists: &[Playlist]) {
+    let path = crate::profiles::current_profile_dir().join("playlists_cache.json");
+    let cache_entry = PlaylistsCacheEntry {
+        user_id: user_id.to_string(),
+        playlists: playlists.to_vec(),
+        timestamp: chrono::Utc::now().timestamp(),
+    };
+
+    if let Ok(json) = serde_json::to_string(&cache_entry) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
+async fn fetch_playlists_from_api(
+    spotify: &SpotifyClient,
+    user_id: &str,
+) -> Result<Vec<Playlist>, String> {
     let mut playlists = Vec::new();
     let mut offset = 0;
     let mut seen_ids = std::collections::HashSet::new();
@@ -190,74 +593,161 @@ pub async fn fetch_all_playlists(
         offset += 50;
     }
 
-    // 2. Save Cache
-    let cache_entry = PlaylistsCacheEntry {
-        user_id: user_id.to_string(),
-        playlists: playlists.clone(),
-        timestamp: chrono::Utc::now().timestamp(),
-    };
+    Ok(playlists)
+}
 
-    if let Ok(json) = serde_json::to_string(&cache_entry) {
-        let _ = fs::write(path, json);
+/// Why the OAuth callback didn't produce an authorization code
+#[derive(Debug)]
+pub enum CallbackError {
+    PortInUse(u16),
+    Io(String),
+    Timeout,
+    Cancelled,
+    /// The user clicked "Cancel" on Spotify's consent page
+    AccessDenied,
+    /// Any other `error` Spotify redirected back with
+    Authorization(String),
+    /// The `state` parameter didn't match the one we sent. Only shown to the
+    /// browser, the login keeps waiting for the real callback
+    StateMismatch,
+}
+
+impl std::fmt::Display for CallbackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallbackError::PortInUse(port) => write!(
+                f,
+                "Port {} is already in use. Close the other application or choose a different callback port.",
+                port
+            ),
+            CallbackError::Io(e) => write!(f, "Callback server failed: {}", e),
+            CallbackError::Timeout => write!(f, "Timed out waiting for Spotify authorization"),
+            CallbackError::Cancelled => write!(f, "Authorization cancelled"),
+            CallbackError::AccessDenied => write!(f, "Access was denied on the Spotify authorization page"),
+            CallbackError::Authorization(e) => write!(f, "Spotify authorization failed: {}", e),
+            CallbackError::StateMismatch => write!(
+                f,
+                "Authorization response didn't match this login attempt. Please try again."
+            ),
+        }
     }
+}
 
-    Ok(playlists)
+async fn bind_callback(port: u16) -> Result<tokio::net::TcpListener, CallbackError> {
+    tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AddrInUse => CallbackError::PortInUse(port),
+            _ => CallbackError::Io(e.to_string()),
+        })
 }
 
-async fn wait_for_callback() -> Result<String, String> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    use tokio::net::TcpListener;
+/// Accept connections until the real OAuth redirect arrives. Anything else
+/// (favicon requests, port scans, other paths) gets a 404 and is skipped.
+async fn wait_for_callback(
+    listener: &tokio::net::TcpListener,
+    expected_state: &str,
+) -> Result<String, CallbackError> {
+    use tokio::io::AsyncReadExt;
 
-    let listener = TcpListener::bind("127.0.0.1:27196").await.map_err(|e| {
-        format!(
-            "Failed to start callback server: {}. Make sure port 27196 is not in use.",
-            e
-        )
-    })?;
+    println!(
+        "Waiting for Spotify callback on {} ...",
+        listener
+            .local_addr()
+            .map(|a| a.to_string())
+            .unwrap_or_default()
+    );
 
-    println!("Waiting for Spotify callback on http://127.0.0.1:27196/callback ...");
+    loop {
+        let (mut socket, _) = listener
+            .accept()
+            .await
+            .map_err(|e| CallbackError::Io(e.to_string()))?;
+
+        // Don't let a silent connection block the real callback
+        let mut buffer = [0u8; 4096];
+        let n =
+            match tokio::time::timeout(std::time::Duration::from_secs(5), socket.read(&mut buffer))
+                .await
+            {
+                Ok(Ok(n)) if n > 0 => n,
+                _ => continue,
+            };
 
-    let (mut socket, _) = listener
-        .accept()
-        .await
-        .map_err(|e| format!("Failed to accept connection: {}", e))?;
+        let request = String::from_utf8_lossy(&buffer[..n]);
 
-    let mut buffer = [0u8; 4096];
-    let n = socket
-        .read(&mut buffer)
-        .await
-        .map_err(|e| format!("Failed to read request: {}", e))?;
+        // Parse: GET /?code=XXX&state=YYY HTTP/1.1
+        let target = request
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("GET "))
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap_or("");
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
 
-    let request = String::from_utf8_lossy(&buffer[..n]);
+        let params: std::collections::HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .collect();
 
-    // Extract code from URL like: GET /callback?code=XXXX HTTP/1.1
-    let code = request
-        .lines()
-        .next()
-        .and_then(|line| {
-            // Parse: GET /callback?code=XXX&state=YYY HTTP/1.1
-            line.split_whitespace().nth(1)
-        })
-        .and_then(|path| {
-            // Parse: /callback?code=XXX&state=YYY
-            if let Some(query_start) = path.find('?') {
-                let query = &path[query_start + 1..];
-                for param in query.split('&') {
-                    if let Some(code) = param.strip_prefix("code=") {
-                        return Some(code.to_string());
-                    }
-                }
+        // Our redirect URI has no path, but older setups registered /callback
+        let is_callback = path == "/" || path == "/callback";
+        if !is_callback || !(params.contains_key("code") || params.contains_key("error")) {
+            respond(&mut socket, "404 Not Found", "Not found").await;
+            continue;
+        }
+
+        // Not from our login, so it can't end it; keep waiting for the real one
+        if params.get("state") != Some(&expected_state) {
+            let page = ERROR_PAGE.replace("{error}", &CallbackError::StateMismatch.to_string());
+            respond(&mut socket, "400 Bad Request", &page).await;
+            continue;
+        }
+
+        let result = if let Some(error) = params.get("error") {
+            match *error {
+                "access_denied" => Err(CallbackError::AccessDenied),
+                other => Err(CallbackError::Authorization(other.to_string())),
             }
-            None
-        })
-        .ok_or_else(|| "No authorization code in callback URL".to_string())?;
+        } else {
+            Ok(params["code"].to_string())
+        };
+
+        match &result {
+            Ok(_) => {
+                respond(&mut socket, "200 OK", SUCCESS_PAGE).await;
+                println!("Authorization code received!");
+            }
+            Err(e) => {
+                // The error text may echo the query string
+                let message = e
+                    .to_string()
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;");
+                let page = ERROR_PAGE.replace("{error}", &message);
+                respond(&mut socket, "400 Bad Request", &page).await;
+            }
+        }
 
-    // Send success response
-    let response = r#"HTTP/1.1 200 OK
-Content-Type: text/html; charset=utf-8
-Connection: close
+        return result;
+    }
+}
 
-<!DOCTYPE html>
+async fn respond(socket: &mut tokio::net::TcpStream, status: &str, body: &str) {
+    use tokio::io::AsyncWriteExt;
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    socket.write_all(response.as_bytes()).await.ok();
+    socket.flush().await.ok();
+}
+
+const SUCCESS_PAGE: &str = r#"<!DOCTYPE html>
 <html>
 <head>
     <title>Spotify Sorter - Connected!</title>
@@ -276,12 +766,80 @@ Connection: close
 </body>
 </html>"#;
 
-    socket.write_all(response.as_bytes()).await.ok();
-    socket.flush().await.ok();
+const ERROR_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Spotify Sorter - Not Connected</title>
+    <style>
+        body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; background: #121212; color: #fff; display: flex; justify-content: center; align-items: center; height: 100vh; margin: 0; }
+        .container { text-align: center; }
+        h1 { color: #e91429; margin-bottom: 10px; }
+        p { color: #b3b3b3; }
+    </style>
+</head>
+<body>
+    <div class="container">
+        <h1>✕ Could not connect to Spotify</h1>
+        <p>{error}</p>
+        <p>Return to Spotify Sorter to try again.</p>
+    </div>
+</body>
+</html>"#;
 
-    println!("Authorization code received!");
+/// Create a private playlist for the user and add it to the cached playlist list
+pub async fn create_playlist(
+    client: &SpotifyClient,
+    user_id: &str,
+    name: &str,
+    description: &str,
+) -> Result<Playlist, String> {
+    use rspotify::model::UserId;
+
+    let uid = UserId::from_id_or_uri(user_id).map_err(|e| format!("Invalid user ID: {}", e))?;
+    let created = client
+        .user_playlist_create(uid, name, Some(false), Some(false), Some(description))
+        .await
+        .map_err(|e| format!("Failed to create playlist: {}", e))?;
+
+    let playlist = Playlist {
+        editable: true,
+        ..Playlist::from_full(&created, user_id)
+    };
+    update_playlists_cache(|playlists| playlists.insert(0, playlist.clone()));
 
-    Ok(code)
+    Ok(playlist)
+}
+
+/// Apply a local change to the cached playlist list, so it stays correct
+/// without refetching every playlist. Keeps the cache's original age.
+pub fn update_playlists_cache(f: impl FnOnce(&mut Vec<Playlist>)) {
+    use std::fs;
+    let path = crate::profiles::current_profile_dir().join("playlists_cache.json");
+
+    if let Ok(content) = fs::read_to_string(&path) {
+        if let Ok(mut cache) = serde_json::from_str::<PlaylistsCacheEntry>(&content) {
+            f(&mut cache.playlists);
+            if let Ok(json) = serde_json::to_string(&cache) {
+                let _ = fs::write(path, json);
+            }
+        }
+    }
+}
+
+/// Remove a playlist from the user's library. Spotify has no real delete:
+/// the owner unfollowing it is what the Spotify app does too.
+pub async fn delete_playlist(client: &SpotifyClient, playlist_id: &str) -> Result<(), String> {
+    use rspotify::model::PlaylistId;
+
+    let pid = PlaylistId::from_id(playlist_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    client
+        .playlist_unfollow(pid)
+        .await
+        .map_err(|e| format!("Failed to delete playlist: {}", e))?;
+
+    update_playlists_cache(|playlists| playlists.retain(|p| p.id != playlist_id));
+    invalidate_playlist_cache(playlist_id);
+    Ok(())
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -294,7 +852,7 @@ pub struct PlaylistCacheEntry {
 pub type PlaylistCache = std::collections::HashMap<String, PlaylistCacheEntry>;
 
 pub async fn fetch_playlist_tracks(
-    client: &AuthCodeSpotify,
+    client: &SpotifyClient,
     playlist_id: &str,
     app_handle: &tauri::AppHandle,
 ) -> Result<(String, Vec<crate::logic::AppTrack>), String> {
@@ -302,6 +860,9 @@ pub async fn fetch_playlist_tracks(
     use rspotify::model::PlaylistId;
     use std::fs;
 
+    let playlist_id = parse_playlist_id(playlist_id)?;
+    let playlist_id = playlist_id.as_str();
+
     // 1. Get Playlist Metadata (snapshot_id)
     let pid = PlaylistId::from_id(playlist_id).map_err(|e| format!("Invalid ID: {}", e))?;
 
@@ -340,10 +901,7 @@ pub async fn fetch_playlist_tracks(
     let playlist_name = playlist.name;
 
     // 2. Check Cache
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-    path.push("Spotify Sorter");
-    fs::create_dir_all(&path).ok();
-    path.push("spotify_cache.json");
+    let path = crate::profiles::current_profile_dir().join("spotify_cache.json");
 
     let mut cache: PlaylistCache = if path.exists() {
         let content = fs::read_to_string(&path).unwrap_or_default();
@@ -449,7 +1007,7 @@ pub async fn fetch_playlist_tracks(
 }
 
 pub async fn update_playlist_items(
-    client: &AuthCodeSpotify,
+    client: &SpotifyClient,
     playlist_id: &str,
     new_uris: Vec<String>,
     old_uris: Option<Vec<String>>,
@@ -457,7 +1015,8 @@ pub async fn update_playlist_items(
 ) -> Result<(), String> {
     use rspotify::model::PlaylistId;
 
-    let pid = PlaylistId::from_id(playlist_id).map_err(|e| format!("Invalid ID: {}", e))?;
+    let playlist_id = parse_playlist_id(playlist_id)?;
+    let pid = PlaylistId::from_id(&playlist_id).map_err(|e| format!("Invalid ID: {}", e))?;
 
     // Check for local files
     let has_local_files = new_uris
@@ -485,7 +1044,7 @@ pub async fn update_playlist_items(
 }
 
 async fn reorder_strategy(
-    client: &AuthCodeSpotify,
+    client: &SpotifyClient,
     pid: rspotify::model::PlaylistId<'_>,
     mut current: Vec<String>,
     target: Vec<String>,
@@ -677,7 +1236,7 @@ async fn reorder_strategy(
 }
 
 async fn replace_strategy(
-    client: &AuthCodeSpotify,
+    client: &SpotifyClient,
     pid: rspotify::model::PlaylistId<'_>,
     new_uris: Vec<String>,
     app_handle: &tauri::AppHandle,
@@ -762,9 +1321,7 @@ async fn replace_strategy(
 }
 
 pub fn invalidate_playlist_cache(playlist_id: &str) {
-    let mut path = dirs::data_local_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-    path.push("Spotify Sorter");
-    path.push("spotify_cache.json");
+    let path = crate::profiles::current_profile_dir().join("spotify_cache.json");
 
     if path.exists() {
         if let Ok(content) = std::fs::read_to_string(&path) {
@@ -779,3 +1336,42 @@ pub fn invalidate_playlist_cache(playlist_id: &str) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "37i9dQZF1DXcBWIGoYBM5M";
+
+    #[test]
+    fn ids_parse_from_ids_uris_and_urls() {
+        for input in [
+            ID.to_string(),
+            format!("  {}  ", ID),
+            format!("spotify:playlist:{}", ID),
+            format!("spotify:user:someone:playlist:{}", ID),
+            format!("https://open.spotify.com/playlist/{}", ID),
+            format!("https://open.spotify.com/playlist/{}?si=abc123", ID),
+            format!("https://open.spotify.com/intl-de/playlist/{}#top", ID),
+            format!("https://open.spotify.com/user/someone/playlist/{}", ID),
+        ] {
+            assert_eq!(parse_playlist_id(&input), Ok(ID.to_string()), "{}", input);
+        }
+        assert_eq!(
+            parse_artist_id(&format!("spotify:artist:{}", ID)),
+            Ok(ID.to_string())
+        );
+        assert_eq!(
+            parse_track_id(&format!("https://open.spotify.com/track/{}", ID)),
+            Ok(ID.to_string())
+        );
+    }
+
+    #[test]
+    fn ids_of_another_kind_or_malformed_are_rejected() {
+        assert!(parse_playlist_id(&format!("spotify:track:{}", ID)).is_err());
+        assert!(parse_artist_id(&format!("https://open.spotify.com/album/{}", ID)).is_err());
+        assert!(parse_track_id("").is_err());
+        assert!(parse_track_id("not an id").is_err());
+    }
+}
@@ -0,0 +1,338 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Upgrades one saved item by one schema version
+pub type Migration = fn(&mut Value);
+
+/// For versions that only changed the document around the items
+pub fn unchanged(_: &mut Value) {}
+
+/// A JSON list file saved with a schema version, e.g. dynamic_configs.json.
+/// Files written before versioning hold a bare list and count as version 0.
+/// `migrations[n]` upgrades an item from version n to n + 1, so the current
+/// version is the number of migrations.
+pub struct ListFile {
+    pub label: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+#[derive(Serialize)]
+struct Document<'a, T> {
+    version: u32,
+    items: &'a [T],
+}
+
+/// Something that couldn't be loaded from a list file. `index` is unset
+/// when the whole file was unreadable.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadIssue {
+    pub file: String,
+    pub index: Option<usize>,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub error: String,
+    /// Copy of the file as it was found, in the same folder
+    pub backup: Option<String>,
+}
+
+/// Issues of the last load of each file, by path
+static LOAD_ISSUES: Mutex<Vec<(PathBuf, LoadIssue)>> = Mutex::new(Vec::new());
+
+/// Issues from the last load of every list file of the current profile
+pub fn load_issues() -> Vec<LoadIssue> {
+    let dir = crate::profiles::current_profile_dir();
+    LOAD_ISSUES
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(path, _)| path.parent() == Some(dir.as_path()))
+        .map(|(_, issue)| issue.clone())
+        .collect()
+}
+
+impl ListFile {
+    fn version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    /// Load the items, migrating older versions. Items that don't parse are
+    /// skipped and reported, and the file is backed up so saving the rest
+    /// doesn't lose them.
+    pub fn load<T: DeserializeOwned>(&self, path: &Path) -> Vec<T> {
+        let Ok(content) = fs::read_to_string(path) else {
+            record(path, Vec::new());
+            return Vec::new();
+        };
+
+        let (items, mut issues) = self.parse(&content);
+        if !issues.is_empty() {
+            let backup = back_up(path, &content);
+            for issue in &mut issues {
+                println!(
+                    "Couldn't load {} #{:?} ({:?}): {}",
+                    self.label, issue.index, issue.name, issue.error
+                );
+                match &backup {
+                    Ok(file) => issue.backup = Some(file.clone()),
+                    Err(e) => issue.error = format!("{} ({})", issue.error, e),
+                }
+            }
+        }
+        record(path, issues);
+        items
+    }
+
+    /// Save the items under the current version
+    pub fn save<T: Serialize>(&self, path: &Path, items: &[T]) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let document = Document {
+            version: self.version(),
+            items,
+        };
+        let json = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to save {}: {}", self.label, e))
+    }
+
+    fn parse<T: DeserializeOwned>(&self, content: &str) -> (Vec<T>, Vec<LoadIssue>) {
+        let document = serde_json::from_str(content)
+            .map_err(|e| format!("Not valid JSON: {}", e))
+            .and_then(|document| self.split(document));
+        let (version, raw_items) = match document {
+            Ok(document) => document,
+            Err(error) => return (Vec::new(), vec![self.issue(None, &Value::Null, error)]),
+        };
+
+        let mut items = Vec::new();
+        let mut issues = Vec::new();
+        for (index, mut raw) in raw_items.into_iter().enumerate() {
+            for migrate in &self.migrations[version as usize..] {
+                migrate(&mut raw);
+            }
+            match T::deserialize(&raw) {
+                Ok(item) => items.push(item),
+                Err(e) => issues.push(self.issue(Some(index), &raw, e.to_string())),
+            }
+        }
+        (items, issues)
+    }
+
+    /// Version and items of a saved document
+    fn split(&self, document: Value) -> Result<(u32, Vec<Value>), String> {
+        let mut document = match document {
+            Value::Array(items) => return Ok((0, items)),
+            Value::Object(document) => document,
+            _ => return Err("Expected a list or a versioned document".to_string()),
+        };
+        let version = document
+            .get("version")
+            .and_then(Value::as_u64)
+            .ok_or("Missing schema version")? as u32;
+        if version > self.version() {
+            return Err(format!(
+                "Saved by a newer version of the app (schema {}, this version reads up to {})",
+                version,
+                self.version()
+            ));
+        }
+        match document.remove("items") {
+            Some(Value::Array(items)) => Ok((version, items)),
+            _ => Err("Missing list of items".to_string()),
+        }
+    }
+
+    fn issue(&self, index: Option<usize>, raw: &Value, error: String) -> LoadIssue {
+        let field = |key: &str| raw.get(key).and_then(Value::as_str).map(String::from);
+        LoadIssue {
+            file: self.label.to_string(),
+            index,
+            id: field("id"),
+            name: field("name").or_else(|| field("title")),
+            error,
+            backup: None,
+        }
+    }
+}
+
+/// A JSON file holding one value, like a map or a settings object. A file
+/// that doesn't parse loads as the default, is backed up and is reported
+/// like the list files, so the next save doesn't lose it unnoticed.
+pub struct DataFile {
+    pub label: &'static str,
+}
+
+impl DataFile {
+    pub fn load<T: DeserializeOwned + Default>(&self, path: &Path) -> T {
+        let Ok(content) = fs::read_to_string(path) else {
+            record(path, Vec::new());
+            return T::default();
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(value) => {
+                record(path, Vec::new());
+                value
+            }
+            Err(e) => {
+                println!("Couldn't load {}: {}", self.label, e);
+                let mut issue = LoadIssue {
+                    file: self.label.to_string(),
+                    index: None,
+                    id: None,
+                    name: None,
+                    error: format!("Not valid JSON: {}", e),
+                    backup: None,
+                };
+                match back_up(path, &content) {
+                    Ok(file) => issue.backup = Some(file),
+                    Err(e) => issue.error = format!("{} ({})", issue.error, e),
+                }
+                record(path, vec![issue]);
+                T::default()
+            }
+        }
+    }
+
+    pub fn save<T: Serialize>(&self, path: &Path, value: &T) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Failed to save {}: {}", self.label, e))
+    }
+}
+
+/// Replace the issues recorded for a file with those of its latest load
+fn record(path: &Path, issues: Vec<LoadIssue>) {
+    let mut all = LOAD_ISSUES.lock().unwrap();
+    all.retain(|(p, _)| p != path);
+    all.extend(issues.into_iter().map(|issue| (path.to_path_buf(), issue)));
+}
+
+/// Copy a file that didn't fully load next to it. Named by content, so
+/// loading the same file again doesn't pile up copies.
+fn back_up(path: &Path, content: &str) -> Result<String, String> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    content.hash(&mut hasher);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("data");
+    let file_name = format!("{}.unreadable-{:016x}.json", stem, hasher.finish());
+    let backup_path = path.with_file_name(&file_name);
+    if !backup_path.exists() {
+        fs::write(&backup_path, content).map_err(|e| format!("Backup failed: {}", e))?;
+    }
+    Ok(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: String,
+        label: String,
+    }
+
+    fn rename_name_to_label(item: &mut Value) {
+        if let Some(name) = item.as_object_mut().and_then(|o| o.remove("name")) {
+            item["label"] = name;
+        }
+    }
+
+    const FILE: ListFile = ListFile {
+        label: "items",
+        migrations: &[rename_name_to_label, unchanged],
+    };
+
+    fn item(id: &str, label: &str) -> Item {
+        Item {
+            id: id.to_string(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn bare_lists_are_migrated_from_version_zero() {
+        let (items, issues) = FILE.parse::<Item>(r#"[{"id": "1", "name": "One"}]"#);
+        assert_eq!(items, vec![item("1", "One")]);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn documents_only_run_later_migrations() {
+        let content = r#"{"version": 1, "items": [{"id": "1", "label": "One", "name": "x"}]}"#;
+        let (items, issues) = FILE.parse::<Item>(content);
+        assert_eq!(items, vec![item("1", "One")]);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn bad_items_are_skipped_and_reported() {
+        let content = r#"{"version": 2, "items": [
+            {"id": "1", "label": "One"},
+            {"id": "2", "name": "Two"},
+            {"id": "3", "label": "Three"}
+        ]}"#;
+        let (items, issues) = FILE.parse::<Item>(content);
+        assert_eq!(items, vec![item("1", "One"), item("3", "Three")]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].index, Some(1));
+        assert_eq!(issues[0].id.as_deref(), Some("2"));
+        assert_eq!(issues[0].name.as_deref(), Some("Two"));
+    }
+
+    #[test]
+    fn unreadable_files_report_one_issue() {
+        for content in [
+            "not json",
+            r#"{"items": []}"#,
+            r#"{"version": 3, "items": []}"#,
+        ] {
+            let (items, issues) = FILE.parse::<Item>(content);
+            assert!(items.is_empty());
+            assert_eq!(issues.len(), 1, "{}", content);
+            assert_eq!(issues[0].index, None);
+        }
+    }
+
+    #[test]
+    fn saved_files_load_back() {
+        let dir = std::env::temp_dir().join(format!("list-file-{}", std::process::id()));
+        let path = dir.join("items.json");
+        FILE.save(&path, &[serde_json::json!({"id": "1", "label": "One"})])
+            .unwrap();
+        assert_eq!(FILE.load::<Item>(&path), vec![item("1", "One")]);
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn unreadable_data_files_are_backed_up_and_reported() {
+        let dir = std::env::temp_dir().join(format!("data-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("map.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let file = DataFile { label: "map" };
+        let map: std::collections::HashMap<String, i64> = file.load(&path);
+        assert!(map.is_empty());
+        let issues: Vec<LoadIssue> = LOAD_ISSUES
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(p, _)| p == &path)
+            .map(|(_, issue)| issue.clone())
+            .collect();
+        assert_eq!(issues.len(), 1);
+        let backup = issues[0].backup.as_ref().unwrap();
+        assert_eq!(fs::read_to_string(dir.join(backup)).unwrap(), "{ not json");
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,222 @@
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VAULT_VERSION: u32 = 1;
+
+/// Where the encryption key comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum KeySource {
+    Machine,    // Bound to this computer, unlocks automatically
+    Passphrase, // Entered by the user, starts locked
+}
+
+/// A derived key together with the salt it was derived with
+#[derive(Clone)]
+pub struct CredentialsKey {
+    source: KeySource,
+    salt: Vec<u8>,
+    key: [u8; 32],
+}
+
+impl std::fmt::Debug for CredentialsKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print key material
+        f.debug_struct("CredentialsKey")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl CredentialsKey {
+    /// New machine-bound key with a fresh salt
+    pub fn machine() -> Result<Self, String> {
+        Self::derive(KeySource::Machine, &machine_secret()?, new_salt())
+    }
+
+    /// New passphrase key with a fresh salt
+    pub fn passphrase(passphrase: &str) -> Result<Self, String> {
+        if passphrase.is_empty() {
+            return Err("Passphrase cannot be empty".to_string());
+        }
+        Self::derive(KeySource::Passphrase, passphrase.as_bytes(), new_salt())
+    }
+
+    pub fn source(&self) -> KeySource {
+        self.source
+    }
+
+    fn derive(source: KeySource, secret: &[u8], salt: Vec<u8>) -> Result<Self, String> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(secret, &salt, &mut key)
+            .map_err(|e| format!("Failed to derive key: {}", e))?;
+        Ok(Self { source, salt, key })
+    }
+}
+
+/// On-disk layout of an encrypted file
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SealedFile {
+    vault_version: u32,
+    key_source: KeySource,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Result of opening an encrypted file
+pub enum Opened<T> {
+    Missing,
+    /// Encrypted with a passphrase that hasn't been entered yet
+    Locked,
+    /// Plain JSON written before encryption existed
+    Legacy(T),
+    Unlocked(T, CredentialsKey),
+}
+
+/// Open an encrypted file. Machine-bound files unlock on their own; passphrase
+/// files only unlock with the key from a previous `unlock` call.
+pub fn open<T: DeserializeOwned>(
+    path: &Path,
+    key: Option<&CredentialsKey>,
+) -> Result<Opened<T>, String> {
+    if !path.exists() {
+        return Ok(Opened::Missing);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read vault: {}", e))?;
+
+    let sealed = match serde_json::from_str::<SealedFile>(&content) {
+        Ok(sealed) => sealed,
+        Err(_) => {
+            let value = serde_json::from_str(&content)
+                .map_err(|e| format!("Unrecognized vault file: {}", e))?;
+            return Ok(Opened::Legacy(value));
+        }
+    };
+
+    let salt = decode(&sealed.salt)?;
+    let key = match sealed.key_source {
+        KeySource::Machine => CredentialsKey::derive(KeySource::Machine, &machine_secret()?, salt)?,
+        KeySource::Passphrase => match key {
+            Some(k) if k.source == KeySource::Passphrase && k.salt == salt => k.clone(),
+            _ => return Ok(Opened::Locked),
+        },
+    };
+
+    let value = decrypt(&sealed, &key)?;
+    Ok(Opened::Unlocked(value, key))
+}
+
+/// Unlock a passphrase-protected file
+pub fn unlock<T: DeserializeOwned>(
+    path: &Path,
+    passphrase: &str,
+) -> Result<(T, CredentialsKey), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read vault: {}", e))?;
+    let sealed: SealedFile =
+        serde_json::from_str(&content).map_err(|_| "File is not encrypted".to_string())?;
+
+    if sealed.key_source != KeySource::Passphrase {
+        return Err("File is not protected by a passphrase".to_string());
+    }
+
+    let key = CredentialsKey::derive(
+        KeySource::Passphrase,
+        passphrase.as_bytes(),
+        decode(&sealed.salt)?,
+    )?;
+    let value = decrypt(&sealed, &key)?;
+    Ok((value, key))
+}
+
+/// Encrypt `value` with `key` and write it to `path`
+pub fn seal<T: Serialize>(path: &Path, value: &T, key: &CredentialsKey) -> Result<(), String> {
+    let plaintext = serde_json::to_vec(value).map_err(|e| e.to_string())?;
+    let nonce: [u8; 12] = rand::random();
+
+    let cipher = Aes256Gcm::new_from_slice(&key.key).map_err(|e| e.to_string())?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+        .map_err(|_| "Failed to encrypt".to_string())?;
+
+    let sealed = SealedFile {
+        vault_version: VAULT_VERSION,
+        key_source: key.source,
+        salt: BASE64.encode(&key.salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    };
+
+    let json = serde_json::to_string_pretty(&sealed).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| format!("Failed to write vault: {}", e))
+}
+
+/// Remove the fallback machine key (only exists where no machine ID is available)
+pub fn wipe_machine_key() {
+    let path = machine_key_path();
+    if path.exists() {
+        fs::remove_file(path).ok();
+    }
+}
+
+fn decrypt<T: DeserializeOwned>(sealed: &SealedFile, key: &CredentialsKey) -> Result<T, String> {
+    let nonce = decode(&sealed.nonce)?;
+    let ciphertext = decode(&sealed.ciphertext)?;
+    // AES-GCM nonces are 96 bits; `Nonce::from_slice` panics on anything else
+    if nonce.len() != 12 {
+        return Err("Corrupt credentials file".to_string());
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(&key.key).map_err(|e| e.to_string())?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| match key.source {
+            KeySource::Passphrase => "Wrong passphrase".to_string(),
+            KeySource::Machine => "Credentials were encrypted on a different machine".to_string(),
+        })?;
+
+    serde_json::from_slice(&plaintext).map_err(|e| format!("Corrupt vault contents: {}", e))
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(value)
+        .map_err(|e| format!("Corrupt vault file: {}", e))
+}
+
+fn new_salt() -> Vec<u8> {
+    rand::random::<[u8; 16]>().to_vec()
+}
+
+fn machine_key_path() -> PathBuf {
+    // Shared by all profiles
+    crate::profiles::root_data_dir().join("machine.key")
+}
+
+/// Secret that only exists on this machine. Uses the OS machine ID, falling
+/// back to a random key file if the platform doesn't provide one.
+fn machine_secret() -> Result<Vec<u8>, String> {
+    if let Ok(id) = machine_uid::get() {
+        return Ok(format!("spotify-sorter:{}", id).into_bytes());
+    }
+
+    let path = machine_key_path();
+    if let Ok(existing) = fs::read(&path) {
+        if existing.len() == 32 {
+            return Ok(existing);
+        }
+    }
+
+    let secret: [u8; 32] = rand::random();
+    fs::write(&path, secret).map_err(|e| format!("Failed to write machine key: {}", e))?;
+    Ok(secret.to_vec())
+}
@@ -0,0 +1,426 @@
+use crate::bans::BanList;
+use crate::logic::AppTrack;
+use crate::spotify::SpotifyClient;
+use rspotify::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// How long search results are reused before searching Spotify again
+const SEARCH_CACHE_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSearch {
+    tracks: Vec<AppTrack>,
+    fetched_at: i64,
+}
+
+/// Track search results by query, so repeated runs over the same tracks
+/// (e.g. scheduled dynamic playlists) don't search Spotify every time
+pub struct SearchCache {
+    entries: HashMap<String, CachedSearch>,
+    dirty: bool,
+}
+
+fn get_search_cache_path() -> PathBuf {
+    crate::profiles::current_profile_dir().join("version_search_cache.json")
+}
+
+impl SearchCache {
+    pub fn load() -> Self {
+        let now = chrono::Utc::now().timestamp();
+        let mut entries: HashMap<String, CachedSearch> =
+            fs::read_to_string(get_search_cache_path())
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_default();
+        entries.retain(|_, cached| now - cached.fetched_at < SEARCH_CACHE_TTL_SECS);
+        Self {
+            entries,
+            dirty: false,
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let json = serde_json::to_string(&self.entries).map_err(|e| e.to_string())?;
+        fs::write(get_search_cache_path(), json)
+            .map_err(|e| format!("Failed to save search cache: {}", e))
+    }
+
+    /// Search tracks, from the cache if the query was searched recently
+    async fn search(
+        &mut self,
+        client: &SpotifyClient,
+        query: &str,
+    ) -> Result<Vec<AppTrack>, String> {
+        if let Some(cached) = self.entries.get(query) {
+            return Ok(cached.tracks.clone());
+        }
+
+        let result = client
+            .search(
+                query,
+                rspotify::model::SearchType::Track,
+                None,
+                None,
+                Some(10),
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let tracks: Vec<AppTrack> = match result {
+            rspotify::model::SearchResult::Tracks(page) => page
+                .items
+                .iter()
+                .filter_map(AppTrack::from_spotify)
+                .collect(),
+            _ => Vec::new(),
+        };
+        self.entries.insert(
+            query.to_string(),
+            CachedSearch {
+                tracks: tracks.clone(),
+                fetched_at: chrono::Utc::now().timestamp(),
+            },
+        );
+        self.dirty = true;
+        Ok(tracks)
+    }
+}
+
+/// Add search results, skipping tracks already found
+fn add_results(all_results: &mut Vec<AppTrack>, results: Vec<AppTrack>) {
+    for app_track in results {
+        if !all_results.iter().any(|r| r.id == app_track.id) {
+            all_results.push(app_track);
+        }
+    }
+}
+
+// Album type priority: single=0, album=1, compilation=2
+fn type_priority(t: &str) -> u8 {
+    match t.to_lowercase().as_str() {
+        "single" => 0,
+        "album" => 1,
+        "compilation" => 2,
+        _ => 3,
+    }
+}
+
+/// Search Spotify for other releases of a track and return the one the
+/// preference (e.g. "Global: Oldest Version") picks, if it beats the
+/// current version. Never downgrades the album type or picks a banned track.
+pub async fn find_better_version(
+    client: &SpotifyClient,
+    app: &tauri::AppHandle,
+    track: &AppTrack,
+    preference: &str,
+    cache: &mut SearchCache,
+    bans: &BanList,
+) -> Option<AppTrack> {
+    // Get all artists from the track
+    let all_artists: Vec<&str> = track
+        .artist_names
+        .split(',')
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .collect();
+
+    let clean_name = clean_title(&track.name);
+
+    debug_info!(
+        app,
+        format!(
+            "Checking track: '{}' (cleaned: '{}')",
+            track.name, clean_name
+        ),
+        format!("Artists: {:?}", all_artists)
+    );
+
+    // Search for EACH artist to find versions credited differently
+    let mut all_search_results: Vec<AppTrack> = Vec::new();
+
+    for artist in &all_artists {
+        let query = format!("track:{} artist:{}", clean_name, artist);
+        debug_search!(app, format!("Searching: {}", query));
+
+        match cache.search(client, &query).await {
+            Ok(results) => {
+                debug_info!(
+                    app,
+                    format!("Found {} results for artist '{}'", results.len(), artist)
+                );
+                add_results(&mut all_search_results, results);
+            }
+            Err(e) => debug_error!(app, format!("Search failed for '{}': {}", artist, e)),
+        }
+    }
+
+    // FALLBACK: Also search without artist filter to catch edge cases
+    let query_no_artist = format!("track:{}", clean_name);
+    debug_search!(
+        app,
+        format!("Fallback search (no artist): {}", query_no_artist)
+    );
+
+    match cache.search(client, &query_no_artist).await {
+        Ok(results) => {
+            debug_info!(app, format!("Fallback found {} results", results.len()));
+            add_results(&mut all_search_results, results);
+        }
+        Err(e) => debug_error!(app, format!("Fallback search failed: {}", e)),
+    }
+
+    // ADDITIONAL: Search without "Original" suffix to find base album versions
+    let base_name = strip_original_suffix(&clean_name);
+    if base_name != clean_name {
+        for artist in &all_artists {
+            let query_base = format!("track:{} artist:{}", base_name, artist);
+            debug_search!(app, format!("Base search (no 'Original'): {}", query_base));
+
+            match cache.search(client, &query_base).await {
+                Ok(results) => {
+                    debug_info!(
+                        app,
+                        format!(
+                            "Base search found {} results for artist '{}'",
+                            results.len(),
+                            artist
+                        )
+                    );
+                    add_results(&mut all_search_results, results);
+                }
+                Err(e) => debug_error!(app, format!("Base search failed for '{}': {}", artist, e)),
+            }
+        }
+    }
+
+    debug_info!(
+        app,
+        format!("Total unique results: {}", all_search_results.len())
+    );
+
+    // Now filter and process results
+    if all_search_results.is_empty() {
+        return None;
+    }
+
+    // Filter candidates from combined search results
+    let original_artists: Vec<&str> = track.artist_names.split(',').map(|a| a.trim()).collect();
+
+    let mut candidates: Vec<AppTrack> = all_search_results
+        .into_iter()
+        .filter(|t| {
+            // Skip if this is the exact same track (same Spotify ID)
+            if t.id == track.id {
+                debug_skipped!(app, format!("SKIPPED (same ID): {}", t.id));
+                return false;
+            }
+
+            if bans.bans(&t.uri, &t.artist_names, &t.album_name) {
+                debug_skipped!(app, format!("SKIPPED (banned): '{}'", t.name));
+                return false;
+            }
+
+            // Title match (relaxed)
+            let title_match = match_titles_relaxed(&t.name, &track.name);
+            if !title_match {
+                debug_rejected!(
+                    app,
+                    format!("REJECTED (title): '{}'", t.name),
+                    format!(
+                        "'{}' vs '{}'",
+                        clean_title(&t.name),
+                        clean_title(&track.name)
+                    )
+                );
+                return false;
+            }
+
+            // Artist match: Check if ANY original artist appears in candidate
+            let candidate_artists: Vec<&str> =
+                t.artist_names.split(',').map(|a| a.trim()).collect();
+
+            let artist_match = original_artists.iter().any(|orig| {
+                candidate_artists
+                    .iter()
+                    .any(|cand| orig.eq_ignore_ascii_case(cand))
+            });
+
+            if !artist_match {
+                debug_rejected!(
+                    app,
+                    format!("REJECTED (artist): '{}'", t.artist_names),
+                    format!("Expected one of: {:?}", original_artists)
+                );
+            } else {
+                debug_passed!(
+                    app,
+                    format!("PASSED: '{}'", t.name),
+                    format!(
+                        "Album: '{}' ({}, {})",
+                        t.album_name, t.release_date, t.album_type
+                    )
+                );
+            }
+
+            artist_match
+        })
+        .collect();
+
+    debug_info!(
+        app,
+        format!("Candidates after filter: {}", candidates.len())
+    );
+
+    if candidates.is_empty() {
+        return None;
+    }
+
+    // Sort: Primary by date, Secondary by album_type (single > album > compilation)
+    candidates.sort_by(|a, b| {
+        let date_a = crate::logic::parse_date_obj(&a.release_date);
+        let date_b = crate::logic::parse_date_obj(&b.release_date);
+
+        // Primary sort by date
+        let date_cmp = match preference {
+            "Artist Only: Oldest Version" | "Global: Oldest Version" => date_a.cmp(&date_b),
+            "Artist Only: Newest Version" | "Global: Newest Version" => date_b.cmp(&date_a),
+            _ => std::cmp::Ordering::Equal,
+        };
+
+        // If dates are equal, prefer by album_type: single > album > compilation
+        if date_cmp == std::cmp::Ordering::Equal {
+            type_priority(&a.album_type).cmp(&type_priority(&b.album_type))
+        } else {
+            date_cmp
+        }
+    });
+
+    // Log sorted candidates
+    println!("      Sorted candidates ({}):", preference);
+    for (i, c) in candidates.iter().enumerate() {
+        println!(
+            "        [{}] '{}' from '{}' ({}, type: '{}')",
+            i, c.name, c.album_name, c.release_date, c.album_type
+        );
+    }
+
+    let best = candidates.into_iter().next()?;
+
+    // Compare with current
+    let current_date = crate::logic::parse_date_obj(&track.release_date);
+    let best_date = crate::logic::parse_date_obj(&best.release_date);
+
+    let current_priority = type_priority(&track.album_type);
+    let best_priority = type_priority(&best.album_type);
+
+    debug_comparison!(
+        app,
+        format!("Best: '{}' ({})", best.name, best.release_date),
+        format!("Type: {}, Priority: {}", best.album_type, best_priority)
+    );
+    debug_comparison!(
+        app,
+        format!("Current: '{}' ({})", track.name, track.release_date),
+        format!("Type: {}, Priority: {}", track.album_type, current_priority)
+    );
+
+    debug_comparison!(
+        app,
+        format!(
+            "Album types: current='{}' (priority {}), best='{}' (priority {})",
+            track.album_type, current_priority, best.album_type, best_priority
+        )
+    );
+
+    // NEVER downgrade (e.g. single→album, album→compilation)
+    if best_priority > current_priority {
+        debug_skipped!(
+            app,
+            format!(
+                "SKIPPED: Won't downgrade from {} to {}",
+                track.album_type, best.album_type
+            )
+        );
+        return None;
+    }
+
+    // Replace if: better date OR same date but better album type (upgrade)
+    let should_replace = match preference {
+        p if p.contains("Oldest") => best_date < current_date,
+        p if p.contains("Newest") => best_date > current_date,
+        _ => false,
+    } || (best_date == current_date && best_priority < current_priority);
+
+    println!("      should_replace: {}", should_replace);
+
+    should_replace.then_some(best)
+}
+
+fn match_titles_relaxed(title1: &str, title2: &str) -> bool {
+    let t1_lower = title1.to_lowercase();
+    let t2_lower = title2.to_lowercase();
+
+    // Special handling for "remix", "vip", "bootleg", "edit" keywords
+    // These indicate fundamentally different versions
+    let strict_keywords = ["remix", "vip", "bootleg", "edit"];
+
+    for kw in strict_keywords {
+        let h1 = t1_lower.contains(kw);
+        let h2 = t2_lower.contains(kw);
+        if h1 != h2 {
+            // One is a remix/vip/etc and the other isn't -> different versions
+            return false;
+        }
+    }
+
+    // Clean both titles
+    let t1 = clean_title(title1);
+    let t2 = clean_title(title2);
+
+    // If they match exactly, we're good
+    if t1 == t2 {
+        return true;
+    }
+
+    // Try stripping "original" and "original mix" from both and compare base titles
+    let t1_base = strip_original_suffix(&t1);
+    let t2_base = strip_original_suffix(&t2);
+
+    t1_base == t2_base
+}
+
+/// Strips common "original" suffixes from a cleaned title
+fn strip_original_suffix(s: &str) -> String {
+    let s = s.trim();
+    // Order matters - check longer patterns first
+    let suffixes = [" original mix", " original"];
+    let mut result = s.to_string();
+    for suffix in suffixes {
+        if result.ends_with(suffix) {
+            result = result[..result.len() - suffix.len()].to_string();
+            break;
+        }
+    }
+    result.trim().to_string()
+}
+
+fn clean_title(s: &str) -> String {
+    // Replace common separators with spaces
+    let s = s.replace(['-', '(', ')', '[', ']', '_'], " ");
+
+    // Remove punctuation and extra whitespace, lowercase
+    s.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -2,6 +2,7 @@ import { useState, useEffect, useRef } from 'react';
 import { SortSection } from './components/SortSection';
 import { DuplicatesSection } from './components/DuplicatesSection';
 import { VersionSection } from './components/VersionSection';
+import { OutputSection } from './components/OutputSection';
 import { DynamicPlaylistSection, DynamicPlaylistConfig } from './components/DynamicPlaylistSection';
 import { DynamicPlaylistModal } from './components/DynamicPlaylistModal';
 import { PlaylistSection } from './components/PlaylistSection';
@@ -11,6 +12,7 @@ import { SetupScreen } from './components/SetupScreen';
 import { BackupRestoreModal } from './components/BackupRestoreModal';
 import { HistoryModal } from './components/HistoryModal';
 import { IgnoredTracksModal } from './components/IgnoredTracksModal';
+import { BanListModal } from './components/BanListModal';
 import { ExportModal } from './components/ExportModal';
 import { ReviewModal } from './components/ReviewModal';
 import { ComparePlaylistsModal } from './components/ComparePlaylistsModal';
@@ -20,7 +22,7 @@ import { AppSettingsModal } from './components/AppSettingsModal';
 import { ChangePortModal } from './components/ChangePortModal';
 
 
-import { useAppStore } from './store';
+import { useAppStore, Playlist } from './store';
 import { invoke } from './tauri-api';
 import { listen } from '@tauri-apps/api/event';
 
@@ -49,9 +51,20 @@ interface ScanResult {
         versions_replaced: number;
         sorted: boolean;
     };
+    // Upstream changes of a fork, pulled in when applied in place
+    fork_sync: { added: number; removed: number } | null;
 }
 
-type ModalType = 'backup' | 'history' | 'ignored' | 'exportAuto' | 'exportCsv' | 'review' | 'compare' | 'm3u' | 'schedules' | 'dynamic_edit' | 'app_settings' | 'change_port' | null;
+interface LoadIssue {
+    file: string;
+    index: number | null;
+    id: string | null;
+    name: string | null;
+    error: string;
+    backup: string | null;
+}
+
+type ModalType = 'backup' | 'history' | 'ignored' | 'bans' | 'exportAuto' | 'exportCsv' | 'review' | 'compare' | 'm3u' | 'schedules' | 'dynamic_edit' | 'app_settings' | 'change_port' | null;
 
 function App() {
     const {
@@ -92,9 +105,15 @@ function App() {
             setActiveModal('change_port');
         });
 
+        // Playlists created or deleted by the backend
+        const unlistenPlaylists = listen<Playlist[]>('playlists-changed', (event) => {
+            useAppStore.getState().setPlaylists(event.payload);
+        });
+
         return () => {
             unlisten.then(f => f());
             unlistenPort.then(f => f());
+            unlistenPlaylists.then(f => f());
         };
     }, []);
 
@@ -105,6 +124,19 @@ function App() {
         } catch (e) {
             console.error('Failed to load dynamic configs:', e);
         }
+        try {
+            const issues = await invoke<LoadIssue[]>('get_load_issues');
+            if (issues.length > 0) {
+                const first = issues[0];
+                const what = first.name ? `'${first.name}'` : first.index !== null ? `entry ${first.index + 1}` : 'the file';
+                setStatus(
+                    `Couldn't load ${issues.length} saved item(s). ${first.file}: ${what}: ${first.error}` +
+                    (first.backup ? ` (original kept as ${first.backup})` : '')
+                );
+            }
+        } catch (e) {
+            console.error('Failed to check saved data:', e);
+        }
     };
 
     const handleSaveDynamicConfig = async (config: DynamicPlaylistConfig) => {
@@ -131,11 +163,14 @@ function App() {
 
     const checkAuth = async () => {
         try {
-            const result = await invoke<{ authenticated: boolean; playlists?: any[] }>('check_auth');
+            const result = await invoke<{ authenticated: boolean; playlists?: any[]; missingScopes?: string[] }>('check_auth');
             if (result.authenticated && result.playlists) {
                 useAppStore.getState().setPlaylists(result.playlists);
                 useAppStore.setState({ isLoggedIn: true });
             }
+            if (result.missingScopes?.length) {
+                setStatus(`Please log out and connect again to grant new permissions: ${result.missingScopes.join(', ')}`);
+            }
         } catch {
             // Not authenticated yet - expected on first load
         }
@@ -188,14 +223,16 @@ function App() {
                         // Push to review queue
                         setReviewQueue(prev => [...prev, result]);
                     } else {
-                        // Auto-apply sort
-                        if (result.stats.sorted && state.sortEnabled) {
+                        // Auto-apply sort and fork updates
+                        const forkChanged = state.outputTarget.type === 'inPlace' && result.fork_sync && (result.fork_sync.added > 0 || result.fork_sync.removed > 0);
+                        if ((result.stats.sorted && state.sortEnabled) || forkChanged) {
                             await invoke('apply_changes', {
                                 playlistId: result.playlist_id,
                                 approvedChanges: [],
                                 rejectedChanges: [],
                                 sortRules: state.sortRules,
                                 sortEnabled: state.sortEnabled,
+                                outputTarget: state.outputTarget,
                             });
                         }
                     }
@@ -239,6 +276,12 @@ function App() {
             return;
         }
 
+        // A single output playlist would be written once per source
+        if (state.outputTarget.type !== 'inPlace' && state.selectedPlaylistIds.length > 1) {
+            setStatus("Saving into another playlist works on one playlist at a time. Select a single playlist or edit in place.");
+            return;
+        }
+
         useAppStore.setState({ isProcessing: true });
 
         // Start background scan loop with a copy of the IDs to respect order
@@ -266,6 +309,7 @@ function App() {
                 rejectedChanges: rejectedChanges,
                 sortRules: state.sortRules,
                 sortEnabled: state.sortEnabled,
+                outputTarget: state.outputTarget,
             });
 
             // If we are done scanning and this was the last review in the queue
@@ -328,16 +372,23 @@ function App() {
                     <SortSection />
                     <DuplicatesSection />
 
-                    <div className="flex justify-end px-1">
+                    <div className="flex justify-end gap-3 px-1">
                         <button
                             onClick={() => setActiveModal('ignored')}
                             className="text-[10px] font-medium text-zinc-500 hover:text-green-400 transition-colors flex items-center gap-1"
                         >
                             Manage ignored tracks
                         </button>
+                        <button
+                            onClick={() => setActiveModal('bans')}
+                            className="text-[10px] font-medium text-zinc-500 hover:text-green-400 transition-colors flex items-center gap-1"
+                        >
+                            Ban list
+                        </button>
                     </div>
 
                     <VersionSection />
+                    <OutputSection />
                     <DynamicPlaylistSection
                         configs={dynamicConfigs}
                         onEdit={(config) => {
@@ -349,6 +400,7 @@ function App() {
                             setActiveModal('dynamic_edit');
                         }}
                         onDelete={handleDeleteDynamicConfig}
+                        onImported={loadDynamicConfigs}
                         onManageSchedules={(id) => {
                             setScheduleInitialConfigId(id);
                             setActiveModal('schedules');
@@ -410,6 +462,9 @@ function App() {
             {activeModal === 'ignored' && (
                 <IgnoredTracksModal onClose={() => setActiveModal(null)} />
             )}
+            {activeModal === 'bans' && (
+                <BanListModal onClose={() => setActiveModal(null)} />
+            )}
             {activeModal === 'exportAuto' && (
                 <ExportModal type="automation" onClose={() => setActiveModal(null)} />
             )}
@@ -441,6 +496,7 @@ function App() {
             {activeModal === 'dynamic_edit' && (
                 <DynamicPlaylistModal
                     config={editingDynamicConfig}
+                    otherConfigs={dynamicConfigs.filter(c => c.id !== editingDynamicConfig?.id)}
                     onSave={handleSaveDynamicConfig}
                     onCancel={() => {
                         setActiveModal(null);
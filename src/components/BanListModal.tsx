@@ -0,0 +1,96 @@
+import { useState, useEffect } from 'react';
+import { X } from 'lucide-react';
+import { invoke } from '../tauri-api';
+
+interface BanList {
+    trackUris: string[];
+    artists: string[];
+    albums: string[];
+}
+
+interface BanListModalProps {
+    onClose: () => void;
+}
+
+const linesOf = (text: string) => text.split('\n').map(l => l.trim()).filter(Boolean);
+
+export function BanListModal({ onClose }: BanListModalProps) {
+    const [tracks, setTracks] = useState('');
+    const [artists, setArtists] = useState('');
+    const [albums, setAlbums] = useState('');
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        invoke<BanList>('get_ban_list')
+            .then(list => {
+                setTracks(list.trackUris.join('\n'));
+                setArtists(list.artists.join('\n'));
+                setAlbums(list.albums.join('\n'));
+            })
+            .catch(console.error);
+    }, []);
+
+    const handleSave = async () => {
+        try {
+            await invoke('save_ban_list', {
+                list: { trackUris: linesOf(tracks), artists: linesOf(artists), albums: linesOf(albums) },
+            });
+            onClose();
+        } catch (e) {
+            setError(`${e}`);
+        }
+    };
+
+    const field = (label: string, placeholder: string, value: string, onChange: (v: string) => void) => (
+        <div className="flex-1 flex flex-col">
+            <label className="block text-[10px] text-zinc-500 mb-1 uppercase tracking-wide">{label}</label>
+            <textarea
+                value={value}
+                onChange={(e) => onChange(e.target.value)}
+                placeholder={placeholder}
+                rows={10}
+                className="w-full bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs font-mono focus:border-green-500/50 focus:outline-none resize-none"
+            />
+        </div>
+    );
+
+    return (
+        <div className="fixed inset-0 bg-black/50 flex items-center justify-center z-50">
+            <div className="bg-zinc-900 border border-zinc-700 rounded-lg w-full max-w-2xl mx-4 flex flex-col">
+                <div data-tauri-drag-region="true" className="flex items-center justify-between p-4 border-b border-zinc-700 bg-zinc-800/50 rounded-t-lg">
+                    <h2 className="text-xl font-bold text-white pointer-events-none">Ban List</h2>
+                    <button onClick={onClose} className="text-zinc-400 hover:text-white transition-colors">
+                        <X size={20} />
+                    </button>
+                </div>
+                <div className="p-4">
+                    <p className="text-xs text-zinc-500 mb-3">
+                        Dynamic playlists and version replacement never add these. One entry per line; artist and album names ignore case.
+                    </p>
+                    <div className="flex gap-3">
+                        {field('Tracks', 'Track links or URIs', tracks, setTracks)}
+                        {field('Artists', 'Artist names', artists, setArtists)}
+                        {field('Albums', 'Album names', albums, setAlbums)}
+                    </div>
+                </div>
+                <div className="flex justify-between items-center p-4 border-t border-zinc-700">
+                    <div className="text-red-400 text-xs">{error}</div>
+                    <div className="flex gap-3">
+                        <button
+                            onClick={onClose}
+                            className="px-4 py-1.5 rounded bg-zinc-800 hover:bg-zinc-700 text-zinc-300 text-xs font-medium border border-zinc-700"
+                        >
+                            Cancel
+                        </button>
+                        <button
+                            onClick={handleSave}
+                            className="px-5 py-1.5 rounded bg-green-600 hover:bg-green-500 text-white text-xs font-bold uppercase tracking-wide"
+                        >
+                            Save
+                        </button>
+                    </div>
+                </div>
+            </div>
+        </div>
+    );
+}
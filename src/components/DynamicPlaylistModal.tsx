@@ -1,7 +1,10 @@
 import { useState } from 'react';
 import { X, Search, Check } from 'lucide-react';
 import { useAppStore } from '../store';
-import { DynamicPlaylistConfig, Source, FilterConfig } from './DynamicPlaylistSection';
+import { DynamicPlaylistConfig, Source, SourceExpr, TrackIdentity, FilterConfig, FilterRule, SampleSeed, Selection, UpdateMode } from './DynamicPlaylistSection';
+import { FilterRulesEditor } from './FilterRulesEditor';
+import { DynamicPreview, DynamicPreviewPanel } from './DynamicPreviewPanel';
+import { invoke } from '../tauri-api';
 
 // Filter constants and component
 const FILTER_OPTIONS = ['All', 'Editable Only', 'Owned by Me', 'Public', 'Private'];
@@ -42,13 +45,61 @@ function FilterDropdown({ value, onChange }: { value: string, onChange: (v: stri
 
 interface Props {
     config: DynamicPlaylistConfig | null;
+    otherConfigs: DynamicPlaylistConfig[];
     onSave: (config: DynamicPlaylistConfig) => void;
     onCancel: () => void;
 }
 
 const generateId = () => Math.random().toString(36).substring(2, 11);
 
-export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
+const TERM_OPTIONS = [
+    { value: 'shortTerm', label: 'Last 4 weeks' },
+    { value: 'mediumTerm', label: 'Last 6 months' },
+    { value: 'longTerm', label: 'All time' },
+];
+
+const PICK_ORDERS: Record<string, { label: string; criteria: string; descending: boolean }> = {
+    newest: { label: 'Newest', criteria: 'Release Date', descending: true },
+    oldest: { label: 'Oldest', criteria: 'Release Date', descending: false },
+    popular: { label: 'Most popular', criteria: 'Popularity', descending: true },
+    longest: { label: 'Longest', criteria: 'Duration', descending: true },
+    shortest: { label: 'Shortest', criteria: 'Duration', descending: false },
+};
+
+const readPickOrder = (selection?: Selection | null) => {
+    const rules = selection?.orderBy || [];
+    if (rules.length === 0) return 'none';
+    const preset = Object.entries(PICK_ORDERS).find(([, o]) =>
+        rules.length === 1 && rules[0].criteria === o.criteria && rules[0].descending === o.descending
+    );
+    return preset ? preset[0] : 'custom';
+};
+
+type CombineMode = 'union' | 'intersection' | 'difference' | 'custom';
+
+// Read back expressions built by this form; anything else is kept as is
+const readExpression = (expression?: SourceExpr | null) => {
+    if (!expression) return { mode: 'union' as CombineMode, identity: 'uri' as TrackIdentity, excludeIds: [] as string[] };
+    const identity = expression.op !== 'source' ? expression.identity || 'uri' : 'uri';
+    if (expression.op === 'union' || expression.op === 'intersection') {
+        return { mode: expression.op as CombineMode, identity, excludeIds: [] };
+    }
+    if (expression.op === 'difference' && expression.from.op === 'union'
+        && expression.subtract.every(e => e.op === 'source' && e.source.type === 'playlist')) {
+        const excludeIds = expression.subtract.map(e => (e.op === 'source' ? e.source.id! : ''));
+        return { mode: 'difference' as CombineMode, identity, excludeIds };
+    }
+    return { mode: 'custom' as CombineMode, identity, excludeIds: [] };
+};
+
+const ALBUM_GROUPS = [
+    { value: 'album', label: 'Albums' },
+    { value: 'single', label: 'Singles' },
+    { value: 'appears_on', label: 'Appears On' },
+    { value: 'compilation', label: 'Compilations' },
+];
+
+export function DynamicPlaylistModal({ config, otherConfigs, onSave, onCancel }: Props) {
     const { playlists, sortEnabled, sortRules, dupesEnabled, dupePreference, versionEnabled, versionPreference } = useAppStore();
 
     const [name, setName] = useState(config?.name || '');
@@ -57,16 +108,58 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
         config?.sources.filter(s => s.type === 'playlist').map(s => s.id!) || []
     );
     const [includeLikedSongs, setIncludeLikedSongs] = useState(config?.includeLikedSongs || false);
+    const artistSources = config?.sources.filter(s => s.type === 'artist') || [];
+    const [artistInput, setArtistInput] = useState(artistSources.map(s => s.id).join('\n'));
+    const [includeGroups, setIncludeGroups] = useState<string[]>(
+        artistSources[0]?.includeGroups || ['album', 'single']
+    );
+    const [newReleasesOnly, setNewReleasesOnly] = useState(artistSources[0]?.newReleasesOnly || false);
+    const [sourceConfigIds, setSourceConfigIds] = useState<string[]>(
+        config?.sources.filter(s => s.type === 'dynamicConfig').map(s => s.id!) || []
+    );
+    const initialCombine = readExpression(config?.sourceExpression);
+    const [combineMode, setCombineMode] = useState<CombineMode>(initialCombine.mode);
+    const [trackIdentity, setTrackIdentity] = useState<TrackIdentity>(initialCombine.identity);
+    const [excludeIds, setExcludeIds] = useState<string[]>(initialCombine.excludeIds);
+    const [includeSavedAlbums, setIncludeSavedAlbums] = useState(
+        config?.sources.some(s => s.type === 'savedAlbums') || false
+    );
+    const topTracksSource = config?.sources.find(s => s.type === 'topTracks');
+    const [includeTopTracks, setIncludeTopTracks] = useState(!!topTracksSource);
+    const [topTracksTerm, setTopTracksTerm] = useState<string>(topTracksSource?.term || 'mediumTerm');
+    const [includeRecentlyPlayed, setIncludeRecentlyPlayed] = useState(
+        config?.sources.some(s => s.type === 'recentlyPlayed') || false
+    );
     const [excludeLiked, setExcludeLiked] = useState(config?.filters.excludeLiked || false);
+    const [excludeIgnored, setExcludeIgnored] = useState(config?.filters.excludeIgnored || false);
+    const [filterRules, setFilterRules] = useState<FilterRule[]>(config?.filters.rules || []);
+    const [ruleMatch, setRuleMatch] = useState<'all' | 'any'>(config?.filters.ruleMatch || 'all');
     const [keywordBlacklist, setKeywordBlacklist] = useState(
         config?.filters.keywordBlacklist.join(', ') || ''
     );
-    const [updateMode, setUpdateMode] = useState<'replace' | 'merge' | 'append'>(
-        config?.updateMode || 'replace'
-    );
+    const [updateMode, setUpdateMode] = useState<UpdateMode>(config?.updateMode || 'replace');
+    const [maxAgeDays, setMaxAgeDays] = useState(config?.rolling?.maxAgeDays?.toString() || '');
+    const [maxTracks, setMaxTracks] = useState(config?.rolling?.maxTracks?.toString() || '');
+    const [maxHours, setMaxHours] = useState(config?.rolling?.maxHours?.toString() || '');
     const [samplePerSource, setSamplePerSource] = useState<string>(
         config?.samplePerSource?.toString() || ''
     );
+    const [sampleTotal, setSampleTotal] = useState<string>(config?.sampleTotal?.toString() || '');
+    const [seedType, setSeedType] = useState<SampleSeed['type']>(config?.sampleSeed?.type || 'random');
+    const [fixedSeed, setFixedSeed] = useState<string>(
+        config?.sampleSeed?.type === 'fixed' ? config.sampleSeed.value.toString() : '1'
+    );
+    const [pickOrder, setPickOrder] = useState(readPickOrder(config?.selection));
+    const [pickLimit, setPickLimit] = useState(config?.selection?.limit?.toString() || '');
+    const [maxPerArtist, setMaxPerArtist] = useState(config?.selection?.maxPerArtist?.toString() || '');
+    const [maxPerAlbum, setMaxPerAlbum] = useState(config?.selection?.maxPerAlbum?.toString() || '');
+    const [sourceWeights, setSourceWeights] = useState<Record<string, string>>(
+        Object.fromEntries(
+            (config?.sources || [])
+                .filter(s => s.type === 'playlist' && s.weight != null)
+                .map(s => [s.id!, s.weight!.toString()])
+        )
+    );
 
     // Processing options - load from config if editing, otherwise use app defaults
     const [applySort, setApplySort] = useState(config?.processing?.applySort ?? sortEnabled);
@@ -117,39 +210,126 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
         setSelectedSourceIds(prev => prev.filter(id => !visibleIds.has(id)));
     };
 
-    const handleSubmit = () => {
+    const [preview, setPreview] = useState<DynamicPreview | null>(null);
+    const [previewing, setPreviewing] = useState(false);
+
+    const buildConfig = (): DynamicPlaylistConfig | null => {
         if (!name.trim()) {
             setError('Please enter a name for this dynamic playlist.');
-            return;
+            return null;
         }
         if (!targetPlaylistId) {
             setError('Please select a target playlist.');
-            return;
+            return null;
+        }
+        const artistIds = artistInput.split('\n').map(a => a.trim()).filter(Boolean);
+        const hasLibrarySource = includeSavedAlbums || includeTopTracks || includeRecentlyPlayed || sourceConfigIds.length > 0;
+        if (selectedSourceIds.length === 0 && !includeLikedSongs && artistIds.length === 0 && !hasLibrarySource) {
+            setError('Please select at least one source playlist or artist, or include Liked Songs.');
+            return null;
+        }
+        if (updateMode === 'rolling' && !maxAgeDays && !maxTracks && !maxHours) {
+            setError('Please set how many days, tracks or hours the rolling playlist keeps.');
+            return null;
         }
-        if (selectedSourceIds.length === 0 && !includeLikedSongs) {
-            setError('Please select at least one source playlist or include Liked Songs.');
-            return;
+        if (artistIds.length > 0 && includeGroups.length === 0) {
+            setError('Please choose which kinds of artist releases to include.');
+            return null;
         }
         setError(null);
 
-        const sources: Source[] = selectedSourceIds.map(id => ({ type: 'playlist', id }));
+        // Sample sizes and weights of unchanged sources are kept
+        const previous = (source: Source) => config?.sources.find(s => s.type === source.type && s.id === source.id);
+        const sources: Source[] = selectedSourceIds.map(id => {
+            const weight = sourceWeights[id] ? parseFloat(sourceWeights[id]) : undefined;
+            const old = previous({ type: 'playlist', id });
+            return { type: 'playlist', id, sampleSize: old?.sampleSize, weight, selection: old?.selection };
+        });
         if (includeLikedSongs) {
             sources.push({ type: 'likedSongs' });
         }
+        artistIds.forEach(id => sources.push({ type: 'artist', id, includeGroups, newReleasesOnly }));
+        if (includeSavedAlbums) {
+            sources.push({ type: 'savedAlbums' });
+        }
+        if (includeTopTracks) {
+            sources.push({ type: 'topTracks', term: topTracksTerm as Source['term'] });
+        }
+        if (includeRecentlyPlayed) {
+            sources.push({ type: 'recentlyPlayed' });
+        }
+        sourceConfigIds.forEach(id => sources.push({ type: 'dynamicConfig', id }));
+        // Keep sources this form doesn't edit
+        const editedTypes = ['playlist', 'likedSongs', 'artist', 'savedAlbums', 'topTracks', 'recentlyPlayed', 'dynamicConfig'];
+        sources.push(...(config?.sources.filter(s => !editedTypes.includes(s.type)) || []));
 
         const filters: FilterConfig = {
             excludeLiked,
+            excludeIgnored,
             keywordBlacklist: keywordBlacklist.split(',').map(k => k.trim()).filter(Boolean),
+            rules: filterRules,
+            ruleMatch,
         };
 
+        sources.forEach(source => {
+            if (source.type !== 'playlist') {
+                const old = previous(source);
+                source.sampleSize = old?.sampleSize;
+                source.weight = old?.weight;
+                source.selection = old?.selection;
+            }
+        });
+
+        const items: SourceExpr[] = sources.map(({ sampleSize: _size, weight: _weight, selection: _selection, ...source }) => ({ op: 'source', source }));
+        let sourceExpression: SourceExpr | null = null;
+        if (combineMode === 'custom') {
+            sourceExpression = config?.sourceExpression || null;
+        } else if (combineMode === 'intersection') {
+            sourceExpression = { op: 'intersection', items, identity: trackIdentity };
+        } else if (combineMode === 'difference' && excludeIds.length > 0) {
+            sourceExpression = {
+                op: 'difference',
+                from: { op: 'union', items, identity: trackIdentity },
+                subtract: excludeIds.map(id => ({ op: 'source', source: { type: 'playlist', id } })),
+                identity: trackIdentity,
+            };
+        } else if (trackIdentity === 'duplicateKey') {
+            sourceExpression = { op: 'union', items, identity: trackIdentity };
+        }
+
+        const orderBy = pickOrder === 'custom'
+            ? config?.selection?.orderBy || []
+            : pickOrder === 'none'
+                ? []
+                : [{ id: 'pick', criteria: PICK_ORDERS[pickOrder].criteria, descending: PICK_ORDERS[pickOrder].descending }];
+        const selection: Selection | null = orderBy.length > 0 || pickLimit || maxPerArtist || maxPerAlbum
+            ? {
+                orderBy,
+                limit: pickLimit ? parseInt(pickLimit) : null,
+                maxPerArtist: maxPerArtist ? parseInt(maxPerArtist) : null,
+                maxPerAlbum: maxPerAlbum ? parseInt(maxPerAlbum) : null,
+            }
+            : null;
+
         const newConfig: DynamicPlaylistConfig = {
             id: config?.id || generateId(),
             name: name.trim(),
             targetPlaylistId,
             sources,
+            sourceExpression,
             filters,
             updateMode,
+            rolling: {
+                maxAgeDays: maxAgeDays ? parseInt(maxAgeDays) : null,
+                maxTracks: maxTracks ? parseInt(maxTracks) : null,
+                maxHours: maxHours ? parseInt(maxHours) : null,
+            },
             samplePerSource: samplePerSource ? parseInt(samplePerSource) : null,
+            sampleTotal: sampleTotal ? parseInt(sampleTotal) : null,
+            sampleSeed: seedType === 'fixed'
+                ? { type: 'fixed', value: parseInt(fixedSeed) || 0 }
+                : { type: seedType },
+            selection,
             includeLikedSongs,
             processing: {
                 applySort,
@@ -159,9 +339,28 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                 dupePreference: dupePreference,
                 versionPreference: versionPreference,
             },
+            importedFrom: config?.importedFrom,
         };
 
-        onSave(newConfig);
+        return newConfig;
+    };
+
+    const handleSubmit = () => {
+        const newConfig = buildConfig();
+        if (newConfig) onSave(newConfig);
+    };
+
+    const handlePreview = async () => {
+        const newConfig = buildConfig();
+        if (!newConfig) return;
+        setPreviewing(true);
+        try {
+            setPreview(await invoke<DynamicPreview>('preview_dynamic_update', { config: newConfig }));
+        } catch (e) {
+            setError(`Preview failed: ${e}`);
+        } finally {
+            setPreviewing(false);
+        }
     };
 
     const selectedTarget = playlists.find(p => p.id === targetPlaylistId);
@@ -298,6 +497,160 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                                 />
                                 <span className="text-xs text-zinc-400 group-hover:text-zinc-300 transition-colors">Include Liked Songs as Source</span>
                             </label>
+                            <label className="flex items-center gap-2 mt-1 cursor-pointer group">
+                                <input
+                                    type="checkbox"
+                                    checked={includeSavedAlbums}
+                                    onChange={(e) => setIncludeSavedAlbums(e.target.checked)}
+                                    className="accent-green-500 rounded-sm"
+                                />
+                                <span className="text-xs text-zinc-400 group-hover:text-zinc-300 transition-colors">Include Saved Albums</span>
+                            </label>
+                            <div className="flex items-center gap-2 mt-1">
+                                <label className="flex items-center gap-2 cursor-pointer group">
+                                    <input
+                                        type="checkbox"
+                                        checked={includeTopTracks}
+                                        onChange={(e) => setIncludeTopTracks(e.target.checked)}
+                                        className="accent-green-500 rounded-sm"
+                                    />
+                                    <span className="text-xs text-zinc-400 group-hover:text-zinc-300 transition-colors">Include Top Tracks</span>
+                                </label>
+                                {includeTopTracks && (
+                                    <select
+                                        value={topTracksTerm}
+                                        onChange={(e) => setTopTracksTerm(e.target.value)}
+                                        className="bg-zinc-900/50 border border-zinc-700/50 rounded px-1.5 py-0.5 text-xs text-white focus:outline-none"
+                                    >
+                                        {TERM_OPTIONS.map(o => <option key={o.value} value={o.value}>{o.label}</option>)}
+                                    </select>
+                                )}
+                            </div>
+                            <label className="flex items-center gap-2 mt-1 cursor-pointer group">
+                                <input
+                                    type="checkbox"
+                                    checked={includeRecentlyPlayed}
+                                    onChange={(e) => setIncludeRecentlyPlayed(e.target.checked)}
+                                    className="accent-green-500 rounded-sm"
+                                />
+                                <span className="text-xs text-zinc-400 group-hover:text-zinc-300 transition-colors">Include Recently Played (last 50)</span>
+                            </label>
+                        </div>
+                    </div>
+
+                    {/* Combine Sources */}
+                    <div className="bg-zinc-800/20 rounded-lg p-3 border border-zinc-800/50">
+                        <h3 className="text-xs font-semibold text-zinc-500 uppercase tracking-wide mb-2">Combine Sources</h3>
+                        {combineMode === 'custom' ? (
+                            <div className="flex items-center gap-2">
+                                <p className="text-[10px] text-zinc-500 flex-1">This playlist uses a custom source expression, which is kept as is.</p>
+                                <button
+                                    onClick={() => setCombineMode('union')}
+                                    className="text-[10px] bg-zinc-800 hover:bg-zinc-700 text-zinc-300 px-2 py-1 rounded"
+                                >
+                                    Replace
+                                </button>
+                            </div>
+                        ) : (
+                            <>
+                                <div className="flex items-center gap-2">
+                                    <select
+                                        value={combineMode}
+                                        onChange={(e) => setCombineMode(e.target.value as CombineMode)}
+                                        className="bg-zinc-900/50 border border-zinc-700/50 rounded px-1.5 py-1 text-xs text-white focus:outline-none"
+                                    >
+                                        <option value="union">Tracks in any source</option>
+                                        <option value="intersection">Tracks in every source</option>
+                                        <option value="difference">Tracks in any source, except...</option>
+                                    </select>
+                                    <select
+                                        value={trackIdentity}
+                                        onChange={(e) => setTrackIdentity(e.target.value as TrackIdentity)}
+                                        className="bg-zinc-900/50 border border-zinc-700/50 rounded px-1.5 py-1 text-xs text-white focus:outline-none"
+                                        title="Whether other releases of the same song (e.g. single and album versions) count as the same track"
+                                    >
+                                        <option value="uri">Match exact tracks</option>
+                                        <option value="duplicateKey">Match same title and artist</option>
+                                    </select>
+                                </div>
+                                {combineMode === 'difference' && (
+                                    <div className="mt-2 max-h-32 overflow-y-auto flex flex-wrap gap-x-4 gap-y-1">
+                                        {playlists.map(p => (
+                                            <label key={p.id} className="flex items-center gap-1.5 cursor-pointer">
+                                                <input
+                                                    type="checkbox"
+                                                    checked={excludeIds.includes(p.id)}
+                                                    onChange={(e) => setExcludeIds(prev => e.target.checked
+                                                        ? [...prev, p.id]
+                                                        : prev.filter(id => id !== p.id))}
+                                                    className="accent-green-500 rounded-sm"
+                                                />
+                                                <span className="text-xs text-zinc-400">{p.name}</span>
+                                            </label>
+                                        ))}
+                                    </div>
+                                )}
+                            </>
+                        )}
+                    </div>
+
+                    {/* Dynamic Playlist Sources */}
+                    {otherConfigs.length > 0 && (
+                        <div className="bg-zinc-800/20 rounded-lg p-3 border border-zinc-800/50">
+                            <h3 className="text-xs font-semibold text-zinc-500 uppercase tracking-wide mb-1">Other Dynamic Playlists</h3>
+                            <p className="text-[10px] text-zinc-600 mb-2">Use their result as a source, computed fresh on every run.</p>
+                            <div className="flex flex-wrap gap-x-4 gap-y-1">
+                                {otherConfigs.map(other => (
+                                    <label key={other.id} className="flex items-center gap-1.5 cursor-pointer">
+                                        <input
+                                            type="checkbox"
+                                            checked={sourceConfigIds.includes(other.id)}
+                                            onChange={(e) => setSourceConfigIds(prev => e.target.checked
+                                                ? [...prev, other.id]
+                                                : prev.filter(id => id !== other.id))}
+                                            className="accent-green-500 rounded-sm"
+                                        />
+                                        <span className="text-xs text-zinc-400">{other.name}</span>
+                                    </label>
+                                ))}
+                            </div>
+                        </div>
+                    )}
+
+                    {/* Artist Sources */}
+                    <div className="bg-zinc-800/20 rounded-lg p-3 border border-zinc-800/50">
+                        <h3 className="text-xs font-semibold text-zinc-500 uppercase tracking-wide mb-1">Artist Sources</h3>
+                        <p className="text-[10px] text-zinc-600 mb-2">One artist link, URI or ID per line.</p>
+                        <textarea
+                            value={artistInput}
+                            onChange={(e) => setArtistInput(e.target.value)}
+                            placeholder="https://open.spotify.com/artist/..."
+                            rows={3}
+                            className="w-full bg-zinc-900/50 border border-zinc-700/50 rounded px-2 py-1.5 text-xs text-white placeholder-zinc-600 focus:outline-none focus:border-green-500/50 font-mono"
+                        />
+                        <div className="flex flex-wrap items-center gap-3 mt-2">
+                            {ALBUM_GROUPS.map(group => (
+                                <label key={group.value} className="flex items-center gap-1.5 cursor-pointer">
+                                    <input
+                                        type="checkbox"
+                                        checked={includeGroups.includes(group.value)}
+                                        onChange={(e) => setIncludeGroups(prev => e.target.checked
+                                            ? [...prev, group.value]
+                                            : prev.filter(g => g !== group.value))}
+                                        className="accent-green-500 rounded-sm"
+                                    />
+                                    <span className="text-xs text-zinc-400">{group.label}</span>
+                                </label>
+                            ))}
+                            <label className="flex items-center gap-1.5 cursor-pointer ml-auto" title="Only releases that weren't there on the previous run. The first run uses releases from the last 4 weeks.">
+                                <input
+                                    type="checkbox"
+                                    checked={newReleasesOnly}
+                                    onChange={(e) => setNewReleasesOnly(e.target.checked)}
+                                    className="accent-green-500 rounded-sm"
+                                />
+                                <span className="text-xs text-zinc-400">New releases since last run</span>
+                            </label>
                         </div>
                     </div>
 
@@ -362,6 +715,15 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                                 />
                                 <span className="text-xs text-zinc-400 group-hover:text-zinc-300">Exclude liked songs</span>
                             </label>
+                            <label className="flex items-center gap-2 cursor-pointer mb-3 group">
+                                <input
+                                    type="checkbox"
+                                    checked={excludeIgnored}
+                                    onChange={(e) => setExcludeIgnored(e.target.checked)}
+                                    className="accent-green-500"
+                                />
+                                <span className="text-xs text-zinc-400 group-hover:text-zinc-300">Exclude ignored tracks</span>
+                            </label>
                             <div className="mt-auto">
                                 <label className="block text-[10px] text-zinc-500 mb-1 uppercase tracking-wide">Exclude keywords (comma separated)</label>
                                 <input
@@ -372,19 +734,29 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                                     className="w-full bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
                                 />
                             </div>
+                            <FilterRulesEditor
+                                rules={filterRules}
+                                ruleMatch={ruleMatch}
+                                onChange={(rules, match) => {
+                                    setFilterRules(rules);
+                                    setRuleMatch(match);
+                                }}
+                            />
                         </div>
 
                         {/* Update Mode & Sample */}
                         <div className="bg-zinc-800/20 rounded-lg p-3 border border-zinc-800/50 flex flex-col h-full">
                             <h3 className="text-xs font-semibold text-zinc-500 uppercase tracking-wide mb-2">Update Mode</h3>
                             <div className="flex gap-1 mb-3 bg-zinc-900 p-0.5 rounded border border-zinc-800">
-                                {(['replace', 'merge', 'append'] as const).map(mode => (
+                                {(['replace', 'merge', 'append', 'rolling', 'sync'] as const).map(mode => (
                                     <button
                                         key={mode}
                                         title={{
                                             replace: "Overwrites the target playlist completely.",
                                             merge: "Adds missing tracks to the target (prevents duplicates).",
-                                            append: "Adds tracks to the end of the target (may create duplicates)."
+                                            append: "Adds tracks to the end of the target (may create duplicates).",
+                                            rolling: "Adds new tracks and drops the oldest additions to stay within a window.",
+                                            sync: "Adds new tracks and removes tracks this playlist added that no longer match. Tracks you added by hand stay."
                                         }[mode]}
                                         onClick={() => setUpdateMode(mode)}
                                         className={`flex-1 px-2 py-1.5 rounded text-[10px] font-medium uppercase tracking-wide transition-all ${updateMode === mode
@@ -396,6 +768,27 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                                     </button>
                                 ))}
                             </div>
+                            {updateMode === 'rolling' && (
+                                <div className="flex gap-1 mb-3">
+                                    {([
+                                        ['Days', maxAgeDays, setMaxAgeDays],
+                                        ['Tracks', maxTracks, setMaxTracks],
+                                        ['Hours', maxHours, setMaxHours],
+                                    ] as const).map(([label, value, setValue]) => (
+                                        <div key={label} className="flex-1">
+                                            <label className="block text-[10px] text-zinc-500 mb-1 uppercase tracking-wide">Max {label}</label>
+                                            <input
+                                                type="number"
+                                                value={value}
+                                                onChange={(e) => setValue(e.target.value)}
+                                                placeholder="-"
+                                                min={1}
+                                                className="w-full bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                            />
+                                        </div>
+                                    ))}
+                                </div>
+                            )}
                             <div className="mt-auto">
                                 <label className="block text-[10px] text-zinc-500 mb-1 uppercase tracking-wide">Sample Limit (per source)</label>
                                 <input
@@ -406,11 +799,106 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                                     min={1}
                                     className="w-full bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
                                 />
+                                <label className="block text-[10px] text-zinc-500 mt-2 mb-1 uppercase tracking-wide">Total Tracks (split by weight)</label>
+                                <input
+                                    type="number"
+                                    value={sampleTotal}
+                                    onChange={(e) => setSampleTotal(e.target.value)}
+                                    placeholder="No total"
+                                    min={1}
+                                    className="w-full bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                />
+                                {sampleTotal && selectedSourceIds.length > 1 && (
+                                    <div className="mt-1 space-y-1 max-h-24 overflow-y-auto">
+                                        {selectedSourceIds.map(id => (
+                                            <div key={id} className="flex items-center gap-1.5">
+                                                <span className="flex-1 truncate text-[10px] text-zinc-400">
+                                                    {playlists.find(p => p.id === id)?.name || id}
+                                                </span>
+                                                <input
+                                                    type="number"
+                                                    value={sourceWeights[id] || ''}
+                                                    onChange={(e) => setSourceWeights(prev => ({ ...prev, [id]: e.target.value }))}
+                                                    placeholder="1"
+                                                    min={0}
+                                                    step={0.5}
+                                                    title="Weight"
+                                                    className="w-12 bg-zinc-900 border border-zinc-700 rounded px-1 py-0.5 text-white text-[10px] focus:border-green-500/50 focus:outline-none"
+                                                />
+                                            </div>
+                                        ))}
+                                    </div>
+                                )}
+                                <label className="block text-[10px] text-zinc-500 mt-2 mb-1 uppercase tracking-wide">Sampling</label>
+                                <div className="flex gap-1">
+                                    <select
+                                        value={seedType}
+                                        onChange={(e) => setSeedType(e.target.value as SampleSeed['type'])}
+                                        className="flex-1 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                    >
+                                        <option value="random">New picks every run</option>
+                                        <option value="daily">Same picks all day</option>
+                                        <option value="weekly">Same picks all week</option>
+                                        <option value="fixed">Fixed seed</option>
+                                    </select>
+                                    {seedType === 'fixed' && (
+                                        <input
+                                            type="number"
+                                            value={fixedSeed}
+                                            onChange={(e) => setFixedSeed(e.target.value)}
+                                            min={0}
+                                            className="w-16 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                        />
+                                    )}
+                                </div>
+                                <label className="block text-[10px] text-zinc-500 mt-2 mb-1 uppercase tracking-wide">Pick</label>
+                                <div className="flex gap-1">
+                                    <select
+                                        value={pickOrder}
+                                        onChange={(e) => setPickOrder(e.target.value)}
+                                        className="flex-1 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                    >
+                                        <option value="none">Any order</option>
+                                        {Object.entries(PICK_ORDERS).map(([key, o]) => (
+                                            <option key={key} value={key}>{o.label}</option>
+                                        ))}
+                                        {pickOrder === 'custom' && <option value="custom">Custom</option>}
+                                    </select>
+                                    <input
+                                        type="number"
+                                        value={pickLimit}
+                                        onChange={(e) => setPickLimit(e.target.value)}
+                                        placeholder="All"
+                                        min={1}
+                                        title="Number of tracks"
+                                        className="w-14 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                    />
+                                </div>
+                                <div className="flex gap-1 mt-1">
+                                    <input
+                                        type="number"
+                                        value={maxPerArtist}
+                                        onChange={(e) => setMaxPerArtist(e.target.value)}
+                                        placeholder="Max / artist"
+                                        min={1}
+                                        className="w-1/2 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                    />
+                                    <input
+                                        type="number"
+                                        value={maxPerAlbum}
+                                        onChange={(e) => setMaxPerAlbum(e.target.value)}
+                                        placeholder="Max / album"
+                                        min={1}
+                                        className="w-1/2 bg-zinc-900 border border-zinc-700 rounded px-2 py-1.5 text-white text-xs focus:border-green-500/50 focus:outline-none"
+                                    />
+                                </div>
                             </div>
                         </div>
                     </div>
                 </div>
 
+                {preview && <DynamicPreviewPanel preview={preview} onClose={() => setPreview(null)} />}
+
                 {/* Footer */}
                 <div className="flex justify-between items-center bg-zinc-800/30 p-4 border-t border-zinc-700 rounded-b-lg">
                     <div className="flex flex-col gap-1">
@@ -431,6 +919,14 @@ export function DynamicPlaylistModal({ config, onSave, onCancel }: Props) {
                         >
                             Cancel
                         </button>
+                        <button
+                            onClick={handlePreview}
+                            disabled={previewing}
+                            title="Show what an update would change, without touching the playlist"
+                            className="px-4 py-1.5 rounded bg-zinc-800 hover:bg-zinc-700 text-zinc-300 text-xs font-medium border border-zinc-700 hover:border-zinc-600 transition-all disabled:opacity-50"
+                        >
+                            {previewing ? 'Previewing...' : 'Preview'}
+                        </button>
                         <button
                             onClick={handleSubmit}
                             className="px-5 py-1.5 rounded bg-green-600 hover:bg-green-500 text-white text-xs font-bold uppercase tracking-wide shadow-lg shadow-green-900/20 transition-all hover:scale-105"
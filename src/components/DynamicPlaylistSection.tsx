@@ -1,17 +1,58 @@
 import { useState } from 'react';
-import { Info, Plus, Trash2, Play, Edit2, Calendar } from 'lucide-react';
+import { Info, Plus, Trash2, Play, Edit2, Calendar, Download, Upload } from 'lucide-react';
 import { invoke } from '../tauri-api';
 
 
 // Types matching backend
 export interface Source {
-    type: 'playlist' | 'likedSongs';
+    type: 'playlist' | 'likedSongs' | 'artist' | 'savedAlbums' | 'topTracks' | 'recentlyPlayed' | 'dynamicConfig';
     id?: string;
+    includeGroups?: string[];
+    newReleasesOnly?: boolean;
+    term?: 'shortTerm' | 'mediumTerm' | 'longTerm';
+    sampleSize?: number;
+    weight?: number;
+    selection?: Selection;
 }
 
+export interface Selection {
+    orderBy: SortRule[];
+    limit?: number | null;
+    maxPerArtist?: number | null;
+    maxPerAlbum?: number | null;
+}
+
+export type SampleSeed =
+    | { type: 'random' }
+    | { type: 'daily' }
+    | { type: 'weekly' }
+    | { type: 'fixed'; value: number };
+
+export type FilterRule =
+    | { type: 'releaseYear'; min?: number | null; max?: number | null }
+    | { type: 'releaseDate'; after?: string | null; before?: string | null }
+    | { type: 'duration'; minMs?: number | null; maxMs?: number | null }
+    | { type: 'albumType'; include: string[]; exclude: string[] }
+    | { type: 'artistAllow'; artists: string[] }
+    | { type: 'artistDeny'; artists: string[] }
+    | { type: 'regex'; field: 'name' | 'artist' | 'album'; pattern: string; exclude: boolean }
+    | { type: 'explicit'; explicit: boolean }
+    | { type: 'popularity'; min?: number | null; max?: number | null };
+
+export type TrackIdentity = 'uri' | 'duplicateKey';
+
+export type SourceExpr =
+    | { op: 'source'; source: Source }
+    | { op: 'union'; items: SourceExpr[]; identity?: TrackIdentity }
+    | { op: 'intersection'; items: SourceExpr[]; identity?: TrackIdentity }
+    | { op: 'difference'; from: SourceExpr; subtract: SourceExpr[]; identity?: TrackIdentity };
+
 export interface FilterConfig {
     excludeLiked: boolean;
+    excludeIgnored?: boolean;
     keywordBlacklist: string[];
+    rules?: FilterRule[];
+    ruleMatch?: 'all' | 'any';
 }
 
 export interface SortRule {
@@ -29,16 +70,30 @@ export interface ProcessingOptions {
     versionPreference: string;
 }
 
+export type UpdateMode = 'replace' | 'merge' | 'append' | 'rolling' | 'sync';
+
+export interface RollingWindow {
+    maxAgeDays?: number | null;
+    maxTracks?: number | null;
+    maxHours?: number | null;
+}
+
 export interface DynamicPlaylistConfig {
     id: string;
     name: string;
     targetPlaylistId: string;
     sources: Source[];
+    sourceExpression?: SourceExpr | null;
     filters: FilterConfig;
-    updateMode: 'replace' | 'merge' | 'append';
+    updateMode: UpdateMode;
+    rolling?: RollingWindow;
     samplePerSource: number | null;
+    sampleTotal?: number | null;
+    sampleSeed?: SampleSeed;
+    selection?: Selection | null;
     includeLikedSongs: boolean;
     processing: ProcessingOptions;
+    importedFrom?: string | null;
 }
 
 const INFO_CONTENT = (
@@ -60,13 +115,15 @@ export function DynamicPlaylistSection({
     onManageSchedules,
     onEdit,
     onAdd,
-    onDelete
+    onDelete,
+    onImported
 }: {
     configs: DynamicPlaylistConfig[],
     onManageSchedules: (id?: string) => void,
     onEdit: (config: DynamicPlaylistConfig) => void,
     onAdd: () => void,
-    onDelete: (id: string) => void
+    onDelete: (id: string) => void,
+    onImported: () => void
 }) {
     const [showHelp, setShowHelp] = useState(false);
     const [updatingId, setUpdatingId] = useState<string | null>(null);
@@ -75,9 +132,10 @@ export function DynamicPlaylistSection({
     const handleUpdate = async (id: string) => {
         setUpdatingId(id);
         try {
-            const count = await invoke<number>('run_dynamic_update', { configId: id });
+            const result = await invoke<string | number>('run_dynamic_update', { configId: id });
             const { message } = await import('@tauri-apps/plugin-dialog');
-            await message(`Updated! ${count} tracks in playlist.`, { title: 'Dynamic Playlist', kind: 'info' });
+            const text = typeof result === 'number' ? `Updated! ${result} tracks in playlist.` : result;
+            await message(text, { title: 'Dynamic Playlist', kind: 'info' });
         } catch (e) {
             console.error('Failed to update:', e);
             const { message } = await import('@tauri-apps/plugin-dialog');
@@ -87,6 +145,37 @@ export function DynamicPlaylistSection({
         }
     };
 
+    const handleExport = async (configIds: string[]) => {
+        const { message } = await import('@tauri-apps/plugin-dialog');
+        try {
+            const result = await invoke<string>('export_config_bundle', { configIds });
+            await message(result, { title: 'Export', kind: 'info' });
+        } catch (e) {
+            await message(`Error: ${e}`, { title: 'Error', kind: 'error' });
+        }
+    };
+
+    const handleImport = async () => {
+        const { open, ask, message } = await import('@tauri-apps/plugin-dialog');
+        const path = await open({
+            multiple: false,
+            title: 'Import dynamic playlists',
+            filters: [{ name: 'JSON', extensions: ['json'] }],
+        });
+        if (!path || typeof path !== 'string') return;
+        const createTargets = await ask(
+            'Create target playlists that aren\'t found by ID or name on this account?',
+            { title: 'Import', kind: 'info' }
+        );
+        try {
+            const result = await invoke<string>('import_config_bundle', { path, createTargets });
+            onImported();
+            await message(result, { title: 'Import', kind: 'info' });
+        } catch (e) {
+            await message(`Error: ${e}`, { title: 'Error', kind: 'error' });
+        }
+    };
+
     const handleDelete = (id: string) => {
         onDelete(id);
         setConfirmDeleteId(null);
@@ -107,6 +196,21 @@ export function DynamicPlaylistSection({
                     >
                         <Calendar size={16} />
                     </button>
+                    <button
+                        onClick={handleImport}
+                        className="p-1 rounded hover:bg-zinc-800 text-zinc-400 hover:text-zinc-200 transition-colors"
+                        title="Import dynamic playlists"
+                    >
+                        <Upload size={16} />
+                    </button>
+                    <button
+                        onClick={() => handleExport(configs.map(c => c.id))}
+                        disabled={configs.length === 0}
+                        className="p-1 rounded hover:bg-zinc-800 text-zinc-400 hover:text-zinc-200 transition-colors disabled:opacity-50"
+                        title="Export all dynamic playlists with their schedules"
+                    >
+                        <Download size={16} />
+                    </button>
                     <button
                         onClick={onAdd}
                         className="p-1 rounded hover:bg-zinc-800 text-green-500 hover:text-green-400 transition-colors"
@@ -175,6 +279,13 @@ export function DynamicPlaylistSection({
                                 >
                                     <Edit2 size={14} />
                                 </button>
+                                <button
+                                    onClick={() => handleExport([config.id])}
+                                    className="p-1 rounded hover:bg-zinc-700 text-zinc-400"
+                                    title="Export"
+                                >
+                                    <Download size={14} />
+                                </button>
                                 {confirmDeleteId === config.id ? (
                                     <div className="flex items-center gap-1 bg-red-900/20 px-2 py-0.5 rounded border border-red-900/30">
                                         <span className="text-red-400 text-[10px] mr-1">Sure?</span>
@@ -0,0 +1,105 @@
+import { X } from 'lucide-react';
+import { useAppStore } from '../store';
+import { Source } from './DynamicPlaylistSection';
+
+export interface PreviewTrack {
+    uri: string;
+    name: string;
+    artist: string;
+    album: string;
+}
+
+export interface SourceStats {
+    source: Source | null;
+    fetched: number;
+    sampled: number;
+    afterFilters: number;
+    afterDedupe: number;
+    inResult: number;
+}
+
+export interface DynamicPreview {
+    tracks: PreviewTrack[];
+    added: PreviewTrack[];
+    removed: PreviewTrack[];
+    moved: PreviewTrack[];
+    sources: SourceStats[];
+}
+
+interface Props {
+    preview: DynamicPreview;
+    onClose: () => void;
+}
+
+export function DynamicPreviewPanel({ preview, onClose }: Props) {
+    const { playlists } = useAppStore();
+
+    const sourceLabel = (source: Source | null) => {
+        if (!source) return 'Combined sources';
+        switch (source.type) {
+            case 'playlist': return playlists.find(p => p.id === source.id)?.name || source.id;
+            case 'likedSongs': return 'Liked Songs';
+            case 'artist': return `Artist ${source.id}`;
+            case 'savedAlbums': return 'Saved albums';
+            case 'topTracks': return 'Top tracks';
+            case 'recentlyPlayed': return 'Recently played';
+            case 'dynamicConfig': return `Dynamic playlist ${source.id}`;
+        }
+    };
+
+    const trackList = (title: string, tracks: PreviewTrack[], color: string) => (
+        <div className="min-w-0">
+            <h4 className={`text-[10px] uppercase tracking-wide mb-1 ${color}`}>{title} ({tracks.length})</h4>
+            <div className="max-h-40 overflow-y-auto space-y-0.5">
+                {tracks.map((t, i) => (
+                    <div key={`${t.uri}-${i}`} className="text-[11px] text-zinc-300 truncate" title={`${t.name} - ${t.artist} (${t.album})`}>
+                        {t.name} <span className="text-zinc-500">- {t.artist}</span>
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+
+    return (
+        <div className="border-t border-zinc-700 bg-zinc-950/60 p-4">
+            <div className="flex items-center justify-between mb-2">
+                <h3 className="text-xs font-semibold text-zinc-400 uppercase tracking-wide">
+                    Preview - {preview.tracks.length} tracks after update
+                </h3>
+                <button onClick={onClose} className="text-zinc-500 hover:text-white" title="Close preview">
+                    <X size={14} />
+                </button>
+            </div>
+            <table className="w-full text-[11px] text-zinc-400 mb-3">
+                <thead>
+                    <tr className="text-[10px] uppercase tracking-wide text-zinc-500 text-right">
+                        <th className="text-left font-normal">Source</th>
+                        <th className="font-normal">Fetched</th>
+                        <th className="font-normal">Sampled</th>
+                        <th className="font-normal">Filtered</th>
+                        <th className="font-normal">Deduped</th>
+                        <th className="font-normal">In result</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {preview.sources.map((s, i) => (
+                        <tr key={i} className="text-right">
+                            <td className="text-left truncate max-w-[200px]">{sourceLabel(s.source)}</td>
+                            <td>{s.fetched}</td>
+                            <td>{s.sampled}</td>
+                            <td>{s.afterFilters}</td>
+                            <td>{s.afterDedupe}</td>
+                            <td className="text-white">{s.inResult}</td>
+                        </tr>
+                    ))}
+                </tbody>
+            </table>
+            <div className="grid grid-cols-4 gap-3">
+                {trackList('Added', preview.added, 'text-green-400')}
+                {trackList('Removed', preview.removed, 'text-red-400')}
+                {trackList('Moved', preview.moved, 'text-yellow-400')}
+                {trackList('Result', preview.tracks, 'text-zinc-400')}
+            </div>
+        </div>
+    );
+}
@@ -0,0 +1,146 @@
+import { X } from 'lucide-react';
+import { FilterRule } from './DynamicPlaylistSection';
+
+const RULE_TYPES: { value: FilterRule['type']; label: string }[] = [
+    { value: 'releaseYear', label: 'Release year' },
+    { value: 'releaseDate', label: 'Release date' },
+    { value: 'duration', label: 'Duration' },
+    { value: 'albumType', label: 'Album type' },
+    { value: 'artistAllow', label: 'Only artists' },
+    { value: 'artistDeny', label: 'Not artists' },
+    { value: 'regex', label: 'Pattern' },
+    { value: 'explicit', label: 'Explicit' },
+    { value: 'popularity', label: 'Popularity' },
+];
+
+const newRule = (type: FilterRule['type']): FilterRule => {
+    switch (type) {
+        case 'albumType': return { type, include: [], exclude: [] };
+        case 'artistAllow':
+        case 'artistDeny': return { type, artists: [] };
+        case 'regex': return { type, field: 'name', pattern: '', exclude: false };
+        case 'explicit': return { type, explicit: false };
+        default: return { type } as FilterRule;
+    }
+};
+
+const numberOrNull = (value: string) => (value === '' ? null : Number(value));
+const listFromText = (value: string) => value.split(',').map(v => v.trim()).filter(Boolean);
+
+const inputClass = 'bg-zinc-900 border border-zinc-700 rounded px-1.5 py-1 text-white text-xs focus:border-green-500/50 focus:outline-none';
+
+interface Props {
+    rules: FilterRule[];
+    ruleMatch: 'all' | 'any';
+    onChange: (rules: FilterRule[], ruleMatch: 'all' | 'any') => void;
+}
+
+export function FilterRulesEditor({ rules, ruleMatch, onChange }: Props) {
+    const update = (index: number, rule: FilterRule) => {
+        onChange(rules.map((r, i) => (i === index ? rule : r)), ruleMatch);
+    };
+
+    const renderFields = (rule: FilterRule, index: number) => {
+        switch (rule.type) {
+            case 'releaseYear':
+            case 'popularity':
+                return (
+                    <>
+                        <input type="number" placeholder="min" value={rule.min ?? ''} onChange={(e) => update(index, { ...rule, min: numberOrNull(e.target.value) })} className={`${inputClass} w-16`} />
+                        <input type="number" placeholder="max" value={rule.max ?? ''} onChange={(e) => update(index, { ...rule, max: numberOrNull(e.target.value) })} className={`${inputClass} w-16`} />
+                    </>
+                );
+            case 'releaseDate':
+                return (
+                    <>
+                        <input type="text" placeholder="after YYYY-MM-DD" value={rule.after ?? ''} onChange={(e) => update(index, { ...rule, after: e.target.value || null })} className={`${inputClass} w-28`} />
+                        <input type="text" placeholder="before YYYY-MM-DD" value={rule.before ?? ''} onChange={(e) => update(index, { ...rule, before: e.target.value || null })} className={`${inputClass} w-28`} />
+                    </>
+                );
+            case 'duration':
+                return (
+                    <>
+                        <input type="number" placeholder="min sec" value={rule.minMs != null ? rule.minMs / 1000 : ''} onChange={(e) => update(index, { ...rule, minMs: e.target.value === '' ? null : Number(e.target.value) * 1000 })} className={`${inputClass} w-16`} />
+                        <input type="number" placeholder="max sec" value={rule.maxMs != null ? rule.maxMs / 1000 : ''} onChange={(e) => update(index, { ...rule, maxMs: e.target.value === '' ? null : Number(e.target.value) * 1000 })} className={`${inputClass} w-16`} />
+                    </>
+                );
+            case 'albumType':
+                return (
+                    <>
+                        <input type="text" placeholder="include: album, single" defaultValue={rule.include.join(', ')} onBlur={(e) => update(index, { ...rule, include: listFromText(e.target.value) })} className={`${inputClass} flex-1`} />
+                        <input type="text" placeholder="exclude: compilation" defaultValue={rule.exclude.join(', ')} onBlur={(e) => update(index, { ...rule, exclude: listFromText(e.target.value) })} className={`${inputClass} flex-1`} />
+                    </>
+                );
+            case 'artistAllow':
+            case 'artistDeny':
+                return (
+                    <input type="text" placeholder="Artist names, comma separated" defaultValue={rule.artists.join(', ')} onBlur={(e) => update(index, { ...rule, artists: listFromText(e.target.value) })} className={`${inputClass} flex-1`} />
+                );
+            case 'regex':
+                return (
+                    <>
+                        <select value={rule.field} onChange={(e) => update(index, { ...rule, field: e.target.value as 'name' | 'artist' | 'album' })} className={inputClass}>
+                            <option value="name">Title</option>
+                            <option value="artist">Artist</option>
+                            <option value="album">Album</option>
+                        </select>
+                        <select value={rule.exclude ? 'exclude' : 'match'} onChange={(e) => update(index, { ...rule, exclude: e.target.value === 'exclude' })} className={inputClass}>
+                            <option value="match">matches</option>
+                            <option value="exclude">doesn't match</option>
+                        </select>
+                        <input type="text" placeholder="regex, e.g. \(live\)" value={rule.pattern} onChange={(e) => update(index, { ...rule, pattern: e.target.value })} className={`${inputClass} flex-1 font-mono`} />
+                    </>
+                );
+            case 'explicit':
+                return (
+                    <select value={rule.explicit ? 'yes' : 'no'} onChange={(e) => update(index, { ...rule, explicit: e.target.value === 'yes' })} className={inputClass}>
+                        <option value="no">Clean only</option>
+                        <option value="yes">Explicit only</option>
+                    </select>
+                );
+        }
+    };
+
+    return (
+        <div className="mt-3">
+            <div className="flex items-center gap-2 mb-1">
+                <label className="text-[10px] text-zinc-500 uppercase tracking-wide">Rules</label>
+                {rules.length > 1 && (
+                    <select
+                        value={ruleMatch}
+                        onChange={(e) => onChange(rules, e.target.value as 'all' | 'any')}
+                        className="bg-zinc-900 border border-zinc-700 rounded px-1 py-0.5 text-[10px] text-zinc-300 focus:outline-none"
+                    >
+                        <option value="all">Match all</option>
+                        <option value="any">Match any</option>
+                    </select>
+                )}
+                <select
+                    value=""
+                    onChange={(e) => e.target.value && onChange([...rules, newRule(e.target.value as FilterRule['type'])], ruleMatch)}
+                    className="ml-auto bg-zinc-900 border border-zinc-700 rounded px-1 py-0.5 text-[10px] text-zinc-300 focus:outline-none"
+                >
+                    <option value="">+ Add rule</option>
+                    {RULE_TYPES.map(t => <option key={t.value} value={t.value}>{t.label}</option>)}
+                </select>
+            </div>
+            <div className="space-y-1">
+                {rules.map((rule, index) => (
+                    <div key={index} className="flex items-center gap-1.5">
+                        <span className="text-[10px] text-zinc-400 w-20 flex-shrink-0">
+                            {RULE_TYPES.find(t => t.value === rule.type)?.label}
+                        </span>
+                        {renderFields(rule, index)}
+                        <button
+                            onClick={() => onChange(rules.filter((_, i) => i !== index), ruleMatch)}
+                            className="text-zinc-500 hover:text-red-400 flex-shrink-0"
+                            title="Remove rule"
+                        >
+                            <X size={12} />
+                        </button>
+                    </div>
+                ))}
+            </div>
+        </div>
+    );
+}
@@ -4,7 +4,7 @@ import { invoke } from '../tauri-api';
 
 interface ReviewChange {
     id: string;
-    type: 'replace' | 'duplicate';
+    type: 'replace' | 'duplicate' | 'add' | 'remove';
     newTitle?: string;
     newArtist?: string;
     newAlbum?: string;
@@ -316,9 +316,22 @@ export function HistoryModal({ onClose, onRestore }: HistoryModalProps) {
                                                                         </div>
                                                                     </div>
                                                                 </div>
+                                                            ) : change.type === 'add' ? (
+                                                                <div className="flex items-start gap-2 bg-zinc-900/50 p-2 rounded border border-zinc-800">
+                                                                    <div className="text-green-400 font-bold px-1.5 py-0.5 bg-green-950/30 rounded text-[10px] mt-0.5">ADD</div>
+                                                                    <div className="flex-1 min-w-0">
+                                                                        <div className="text-zinc-400 truncate w-full flex items-baseline gap-2">
+                                                                            <span className="text-green-400 font-medium">{change.newTitle}</span>
+                                                                            <span className="text-zinc-600 text-[10px]">{change.newDate}</span>
+                                                                        </div>
+                                                                        <div className="text-zinc-500 text-[10px] truncate" title={`${change.newArtist} • ${change.newAlbum}`}>
+                                                                            {change.newArtist} • {change.newAlbum}
+                                                                        </div>
+                                                                    </div>
+                                                                </div>
                                                             ) : (
                                                                 <div className="flex items-start gap-2 bg-zinc-900/50 p-2 rounded border border-zinc-800">
-                                                                    <div className="text-orange-400 font-bold px-1.5 py-0.5 bg-orange-950/30 rounded text-[10px] mt-0.5">DUP</div>
+                                                                    <div className="text-orange-400 font-bold px-1.5 py-0.5 bg-orange-950/30 rounded text-[10px] mt-0.5">{change.type === 'remove' ? 'REM' : 'DUP'}</div>
                                                                     <div className="flex-1 min-w-0">
                                                                         <div className="text-zinc-400 truncate w-full flex items-baseline gap-2">
                                                                             <span className="text-red-400 font-medium">{change.remTitle}</span>
@@ -0,0 +1,54 @@
+import { useAppStore } from '../store';
+import { Dropdown } from './ui/Dropdown';
+
+const MODE_OPTIONS = [
+    { value: 'inPlace', label: 'Edit playlists in place' },
+    { value: 'newPlaylist', label: 'Save into a new playlist' },
+    { value: 'existingPlaylist', label: 'Save into an existing playlist' },
+];
+
+export function OutputSection() {
+    const { outputTarget, setOutputTarget, playlists } = useAppStore();
+    const editable = playlists.filter(p => p.editable);
+
+    const handleModeChange = (mode: string) => {
+        if (mode === 'newPlaylist') {
+            setOutputTarget({ type: 'newPlaylist' });
+        } else if (mode === 'existingPlaylist') {
+            setOutputTarget({ type: 'existingPlaylist', id: editable[0]?.id || '' });
+        } else {
+            setOutputTarget({ type: 'inPlace' });
+        }
+    };
+
+    return (
+        <div className="bg-zinc-900/50 border border-zinc-800 rounded-lg p-2.5 space-y-2">
+            <span className="text-green-500 font-semibold text-sm tracking-tight">Output</span>
+            <Dropdown
+                value={outputTarget.type}
+                onChange={handleModeChange}
+                options={MODE_OPTIONS}
+                className="w-full"
+            />
+
+            {outputTarget.type === 'newPlaylist' && (
+                <input
+                    type="text"
+                    value={outputTarget.name || ''}
+                    onChange={(e) => setOutputTarget({ type: 'newPlaylist', name: e.target.value })}
+                    placeholder="Name (default: '<playlist> (Processed)')"
+                    className="w-full bg-zinc-800 text-white border border-zinc-700 rounded px-2 py-1 text-sm"
+                />
+            )}
+
+            {outputTarget.type === 'existingPlaylist' && (
+                <Dropdown
+                    value={outputTarget.id}
+                    onChange={(id) => setOutputTarget({ type: 'existingPlaylist', id })}
+                    options={editable.map(p => ({ value: p.id, label: p.name }))}
+                    className="w-full"
+                />
+            )}
+        </div>
+    );
+}
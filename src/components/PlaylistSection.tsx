@@ -1,5 +1,6 @@
 import { useMemo, useState, useCallback, useEffect, useRef } from 'react';
-import { useAppStore } from '../store';
+import { useAppStore, Playlist } from '../store';
+import { invoke } from '../tauri-api';
 import { Dropdown } from './ui/Dropdown';
 
 const FILTER_OPTIONS = ['All', 'Editable Only', 'Owned by Me', 'Collaborative', 'Public', 'Private'];
@@ -16,6 +17,69 @@ export function PlaylistSection() {
     } = useAppStore();
 
     const [lastClickedIndex, setLastClickedIndex] = useState<number | null>(null);
+    const [forkingId, setForkingId] = useState<string | null>(null);
+
+    const handleFork = async (playlistId: string) => {
+        setForkingId(playlistId);
+        try {
+            const result = await invoke<{ playlist: any; localPlaceholders: any[] }>('fork_playlist', { playlistId });
+            useAppStore.getState().setStatus(
+                result.localPlaceholders.length > 0
+                    ? `Forked ${result.playlist.name} (${result.localPlaceholders.length} local files must be added in Spotify)`
+                    : `Forked ${result.playlist.name}`
+            );
+        } catch (e) {
+            useAppStore.getState().setStatus(`Fork failed: ${e}`);
+        }
+        setForkingId(null);
+    };
+
+    const [refreshing, setRefreshing] = useState(false);
+
+    const handleRefresh = async () => {
+        setRefreshing(true);
+        try {
+            const result = await invoke<{ playlists: Playlist[]; changes: { added: string[]; removed: string[]; changed: string[] } }>('refresh_playlists');
+            const store = useAppStore.getState();
+            store.setPlaylists(result.playlists);
+            store.setSelectedPlaylistIds(store.selectedPlaylistIds.filter(id => result.playlists.some(p => p.id === id)));
+            const { added, removed, changed } = result.changes;
+            store.setStatus(`Playlists refreshed: ${added.length} new, ${removed.length} removed, ${changed.length} changed`);
+        } catch (e) {
+            useAppStore.getState().setStatus(`Refresh failed: ${e}`);
+        }
+        setRefreshing(false);
+    };
+
+    const handleDelete = async (playlist: Playlist) => {
+        if (!window.confirm(`Delete "${playlist.name}"? A backup of its tracks is saved first.`)) return;
+        try {
+            const message = await invoke<string>('delete_playlist', { playlistId: playlist.id });
+            setSelectedPlaylistIds(selectedPlaylistIds.filter(id => id !== playlist.id));
+            useAppStore.getState().setStatus(message);
+        } catch (e) {
+            useAppStore.getState().setStatus(`Delete failed: ${e}`);
+        }
+    };
+
+    // Pasting a playlist link or URI and pressing Enter opens playlists outside the library
+    const handleSearchKeyDown = async (event: React.KeyboardEvent<HTMLInputElement>) => {
+        const input = searchQuery.trim();
+        if (event.key !== 'Enter' || !/open\.spotify\.com\/|^spotify:/.test(input)) return;
+        try {
+            const playlist = await invoke<any>('open_playlist', { input });
+            const store = useAppStore.getState();
+            if (!store.playlists.some(p => p.id === playlist.id)) {
+                store.setPlaylists([playlist, ...store.playlists]);
+            }
+            setSearchQuery('');
+            setSelectedPlaylistIds([playlist.id]);
+            store.setStatus(`Opened ${playlist.name}`);
+        } catch (e) {
+            useAppStore.getState().setStatus(`Could not open playlist: ${e}`);
+        }
+    };
+
     const [isDragging, setIsDragging] = useState(false);
     const [dragStartIndex, setDragStartIndex] = useState<number | null>(null);
 
@@ -241,12 +305,21 @@ export function PlaylistSection() {
                     <span className="text-zinc-500 text-[10px] whitespace-nowrap ml-auto hidden sm:block">
                         {filteredPlaylists.length} of {playlists.length}
                     </span>
+                    <button
+                        onClick={handleRefresh}
+                        disabled={refreshing || playlists.length === 0}
+                        className="text-[10px] bg-zinc-800 hover:bg-zinc-700 text-zinc-300 px-1.5 py-0.5 rounded disabled:opacity-50 ml-auto sm:ml-0"
+                        title="Reload the playlist list from Spotify"
+                    >
+                        {refreshing ? 'Refreshing...' : 'Refresh'}
+                    </button>
                 </div>
                 <input
                     type="text"
-                    placeholder="Search playlists..."
+                    placeholder="Search playlists or paste a link..."
                     value={searchQuery}
                     onChange={(e) => setSearchQuery(e.target.value)}
+                    onKeyDown={handleSearchKeyDown}
                     className="w-full bg-zinc-800 text-white border border-zinc-700 rounded px-2.5 py-1.5 text-xs placeholder-zinc-500"
                 />
             </div>
@@ -291,16 +364,45 @@ export function PlaylistSection() {
                                     }}
                                     className="w-3.5 h-3.5 accent-green-500 cursor-pointer"
                                 />
-                                <span className="flex-1 truncate text-xs">{playlist.name}</span>
+                                <span
+                                    className="flex-1 truncate text-xs"
+                                    title={playlist.ownerName ? `by ${playlist.ownerName}` : undefined}
+                                >
+                                    {playlist.name}
+                                </span>
+                                {playlist.trackCount !== undefined && (
+                                    <span className="text-[10px] text-zinc-500 flex-shrink-0">{playlist.trackCount}</span>
+                                )}
                                 {!playlist.editable && (
                                     <span className="text-[10px] bg-zinc-600/50 text-zinc-400 px-1 py-0.5 rounded flex-shrink-0 hidden sm:block">Read-only</span>
                                 )}
+                                {!playlist.editable && (
+                                    <button
+                                        onMouseDown={(e) => e.stopPropagation()}
+                                        onClick={() => handleFork(playlist.id)}
+                                        disabled={forkingId !== null}
+                                        className="text-[10px] bg-green-600/20 hover:bg-green-600/40 text-green-400 px-1 py-0.5 rounded flex-shrink-0 disabled:opacity-50"
+                                        title="Copy into an editable playlist that follows this one"
+                                    >
+                                        {forkingId === playlist.id ? 'Forking...' : 'Fork'}
+                                    </button>
+                                )}
                                 {playlist.collaborative && (
                                     <span className="text-[10px] bg-blue-600/30 text-blue-400 px-1 py-0.5 rounded flex-shrink-0 hidden sm:block">Collab</span>
                                 )}
                                 {playlist.isPublic && (
                                     <span className="text-[10px] bg-purple-600/30 text-purple-400 px-1 py-0.5 rounded flex-shrink-0 hidden sm:block">Public</span>
                                 )}
+                                {playlist.editable && (
+                                    <button
+                                        onMouseDown={(e) => e.stopPropagation()}
+                                        onClick={() => handleDelete(playlist)}
+                                        className="text-[10px] text-zinc-500 hover:text-red-400 px-1 flex-shrink-0"
+                                        title="Delete playlist"
+                                    >
+                                        ✕
+                                    </button>
+                                )}
                             </div>
                         ))}
                     </div>
@@ -6,13 +6,14 @@ import { ExternalLink, Music } from 'lucide-react';
 export function SetupScreen() {
     const [clientId, setClientId] = useState('');
     const [clientSecret, setClientSecret] = useState('');
+    const [callbackPort, setCallbackPort] = useState(27196);
     const [isConnecting, setIsConnecting] = useState(false);
     const [error, setError] = useState('');
     const [status, setStatus] = useState('');
 
     const handleConnect = async () => {
-        if (!clientId.trim() || !clientSecret.trim()) {
-            setError('Please enter both Client ID and Client Secret');
+        if (!clientId.trim()) {
+            setError('Please enter your Client ID');
             return;
         }
 
@@ -23,7 +24,9 @@ export function SetupScreen() {
         try {
             const result = await invoke<{ success: boolean; playlists?: any[]; error?: string }>('initialize_spotify', {
                 clientId: clientId.trim(),
-                clientSecret: clientSecret.trim(),
+                // Leaving the secret empty uses the PKCE flow
+                clientSecret: clientSecret.trim() || null,
+                callbackPort: Number(callbackPort),
             });
 
             if (result.success && result.playlists) {
@@ -40,6 +43,10 @@ export function SetupScreen() {
         setIsConnecting(false);
     };
 
+    const handleCancel = async () => {
+        await invoke('cancel_auth');
+    };
+
     const openDeveloperDashboard = async () => {
         try {
             await invoke('open_url', { url: 'https://developer.spotify.com/dashboard' });
@@ -64,7 +71,7 @@ export function SetupScreen() {
                         <li>Create a new App (or use an existing one)</li>
                         <li>In Settings, add this Redirect URI:
                             <code className="block mt-1 bg-zinc-800 text-green-400 px-2 py-1 rounded text-xs select-all">
-                                http://127.0.0.1:27196/callback
+                                http://127.0.0.1:{callbackPort}
                             </code>
                         </li>
                         <li>Copy your Client ID and Client Secret below</li>
@@ -92,15 +99,29 @@ export function SetupScreen() {
                     </div>
 
                     <div>
-                        <label className="block text-zinc-400 text-sm font-semibold mb-1">CLIENT SECRET</label>
+                        <label className="block text-zinc-400 text-sm font-semibold mb-1">CLIENT SECRET <span className="font-normal text-zinc-500">(optional)</span></label>
                         <input
                             type="password"
                             value={clientSecret}
                             onChange={(e) => setClientSecret(e.target.value)}
                             disabled={isConnecting}
                             className="w-full bg-zinc-800 text-white border border-zinc-700 rounded px-3 py-2 disabled:opacity-50"
-                            placeholder="Paste your Client Secret here"
+                            placeholder="Leave empty to connect with PKCE"
+                        />
+                    </div>
+
+                    <div>
+                        <label className="block text-zinc-400 text-sm font-semibold mb-1">CALLBACK PORT</label>
+                        <input
+                            type="number"
+                            min="1024"
+                            max="65535"
+                            value={callbackPort}
+                            onChange={(e) => setCallbackPort(parseInt(e.target.value))}
+                            disabled={isConnecting}
+                            className="w-full bg-zinc-800 text-white border border-zinc-700 rounded px-3 py-2 disabled:opacity-50"
                         />
+                        <p className="text-[10px] text-zinc-500 mt-1">Default is 27196. Must match the Redirect URI above.</p>
                     </div>
 
                     {status && !error && (
@@ -127,6 +148,12 @@ export function SetupScreen() {
                         <p className="text-zinc-500 text-xs text-center">
                             A browser window should open for you to authorize with Spotify.
                             After authorizing, you'll be redirected back automatically.
+                            <button
+                                onClick={handleCancel}
+                                className="block mx-auto mt-2 text-zinc-400 hover:text-white underline"
+                            >
+                                Cancel
+                            </button>
                         </p>
                     )}
                     <div className="mt-8 text-center">
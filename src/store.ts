@@ -13,8 +13,16 @@ export interface Playlist {
     editable: boolean;
     collaborative: boolean;
     isPublic: boolean;
+    ownerName?: string;
+    trackCount?: number;
+    snapshotId?: string;
 }
 
+export type OutputTarget =
+    | { type: 'inPlace' }
+    | { type: 'newPlaylist'; name?: string }
+    | { type: 'existingPlaylist'; id: string };
+
 export interface AppState {
     // Auth
     isLoggedIn: boolean;
@@ -32,6 +40,9 @@ export interface AppState {
     versionEnabled: boolean;
     versionPreference: string;
 
+    // Where processed tracks are written
+    outputTarget: OutputTarget;
+
     // Playlists
     playlists: Playlist[];
     selectedPlaylistIds: string[];
@@ -52,6 +63,7 @@ export interface AppState {
     setDupePreference: (pref: string) => void;
     setVersionEnabled: (enabled: boolean) => void;
     setVersionPreference: (pref: string) => void;
+    setOutputTarget: (target: OutputTarget) => void;
     setFilterType: (filter: string) => void;
     setSearchQuery: (query: string) => void;
     togglePlaylistSelection: (id: string) => void;
@@ -90,6 +102,8 @@ export const useAppStore = create<AppState>()(
             versionEnabled: false,
             versionPreference: 'Artist Only: Oldest Version',
 
+            outputTarget: { type: 'inPlace' },
+
             playlists: [],
             selectedPlaylistIds: [],
             filterType: 'Editable Only',
@@ -133,6 +147,7 @@ export const useAppStore = create<AppState>()(
             setDupePreference: (pref) => set({ dupePreference: pref }),
             setVersionEnabled: (enabled) => set({ versionEnabled: enabled }),
             setVersionPreference: (pref) => set({ versionPreference: pref }),
+            setOutputTarget: (target) => set({ outputTarget: target }),
             setFilterType: (filter) => set({ filterType: filter }),
             setSearchQuery: (query) => set({ searchQuery: query }),
 
@@ -169,6 +184,7 @@ export const useAppStore = create<AppState>()(
                 dupePreference: state.dupePreference,
                 versionEnabled: state.versionEnabled,
                 versionPreference: state.versionPreference,
+                outputTarget: state.outputTarget,
                 filterType: state.filterType,
                 selectedPlaylistIds: state.selectedPlaylistIds,
             }),